@@ -0,0 +1,416 @@
+use crate::journal::Date;
+use crate::workspace::Workspace;
+use crate::workspace_files::temp_path;
+use std::{
+    fs,
+    io::{self, BufWriter, Read, Write},
+    ops::ControlFlow,
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// The format of an archive written by [`Workspace::archive_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// An uncompressed POSIX ustar archive.
+    Tar,
+    /// A zip archive. Files are stored without compression.
+    Zip,
+}
+
+/// A file to be archived, with its path relative to the workspace root.
+/// The contents are only read while the archive is written.
+struct ArchiveEntry {
+    name: String,
+    path: PathBuf,
+    size: u64,
+    modified: u64,
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Joins the components of a relative path with `/`, as archive formats require.
+fn entry_name(relative: &Path) -> io::Result<String> {
+    relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(component) => component.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|components| components.join("/"))
+        .ok_or_else(|| {
+            invalid_input(format!(
+                "\"{}\" cannot be stored in an archive",
+                relative.display()
+            ))
+        })
+}
+
+/// Copies exactly the size recorded in the header of an entry into `out`, failing if
+/// the file changed size since, as the header would no longer match the contents.
+fn copy_entry(entry: &ArchiveEntry, out: &mut impl Write) -> io::Result<()> {
+    let file = fs::File::open(&entry.path)?;
+    let copied = io::copy(&mut file.take(entry.size), out)?;
+
+    if copied != entry.size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("\"{}\" changed while it was archived", entry.path.display()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Writes `value` as a NUL-terminated, zero-padded octal number filling `field`.
+fn write_octal(field: &mut [u8], value: u64) -> io::Result<()> {
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+
+    if digits.len() >= field.len() {
+        return Err(invalid_input(format!(
+            "{value} is too large for a tar header"
+        )));
+    }
+
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+
+    Ok(())
+}
+
+fn tar_header(entry: &ArchiveEntry) -> io::Result<[u8; 512]> {
+    let mut header = [0; 512];
+
+    // Names longer than the 100 byte name field are split at a `/` into a prefix.
+    let name = entry.name.as_bytes();
+    let (prefix, name) = if name.len() <= 100 {
+        (&[][..], name)
+    } else {
+        let split = (0..name.len())
+            .filter(|i| name[*i] == b'/')
+            .find(|i| *i <= 155 && name.len() - i - 1 <= 100)
+            .ok_or_else(|| {
+                invalid_input(format!("\"{}\" is too long for a tar archive", entry.name))
+            })?;
+
+        (&name[..split], &name[split + 1..])
+    };
+
+    header[..name.len()].copy_from_slice(name);
+    write_octal(&mut header[100..108], 0o644)?;
+    write_octal(&mut header[108..116], 0)?;
+    write_octal(&mut header[116..124], 0)?;
+    write_octal(&mut header[124..136], entry.size)?;
+    write_octal(&mut header[136..148], entry.modified)?;
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix);
+
+    // The checksum is computed with the checksum field itself filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|byte| u64::from(*byte)).sum();
+    write_octal(&mut header[148..155], checksum)?;
+
+    Ok(header)
+}
+
+fn write_tar(out: &mut impl Write, entries: &[ArchiveEntry]) -> io::Result<()> {
+    for entry in entries {
+        out.write_all(&tar_header(entry)?)?;
+        copy_entry(entry, out)?;
+
+        let padding = entry.size.next_multiple_of(512) - entry.size;
+        out.write_all(&[0; 512][..padding as usize])?;
+    }
+
+    // The end of the archive is marked by two empty blocks.
+    out.write_all(&[0; 1024])
+}
+
+/// Continues a CRC-32 over `data`, starting from `!0` and inverted once at the end.
+fn update_crc32(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+fn crc32(data: &[u8]) -> u32 {
+    !update_crc32(!0, data)
+}
+
+/// Computes the CRC-32 of everything written through it.
+struct Crc32Writer<'a, W> {
+    inner: &'a mut W,
+    crc: u32,
+}
+
+impl<W: Write> Write for Crc32Writer<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc = update_crc32(self.crc, &buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Converts seconds since the Unix epoch into an MS-DOS time and date, as used by zip.
+/// Times before 1980, the earliest MS-DOS date, are clamped to it.
+fn dos_date_time(seconds: u64) -> (u16, u16) {
+    let date = Date::from_days_since_epoch((seconds / 86_400) as i64);
+    if date.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+
+    let time = seconds % 86_400;
+    let dos_time = ((time / 3600) << 11) | ((time / 60 % 60) << 5) | ((time % 60) / 2);
+    let dos_date = (((date.year() - 1980) as u32) << 9) | (date.month() << 5) | date.day();
+
+    (dos_time as u16, dos_date as u16)
+}
+
+/// The fields shared by the local header and the central directory: version needed,
+/// flags (UTF-8 names and a trailing data descriptor), method (stored), time, date,
+/// CRC-32, sizes and name length.
+fn zip_common_fields(entry: &ArchiveEntry, crc: u32, size: u32, name_len: u16) -> Vec<u8> {
+    let (time, date) = dos_date_time(entry.modified);
+
+    let mut common = Vec::with_capacity(26);
+    common.extend_from_slice(&20_u16.to_le_bytes());
+    common.extend_from_slice(&0x0808_u16.to_le_bytes());
+    common.extend_from_slice(&0_u16.to_le_bytes());
+    common.extend_from_slice(&time.to_le_bytes());
+    common.extend_from_slice(&date.to_le_bytes());
+    common.extend_from_slice(&crc.to_le_bytes());
+    common.extend_from_slice(&size.to_le_bytes());
+    common.extend_from_slice(&size.to_le_bytes());
+    common.extend_from_slice(&name_len.to_le_bytes());
+
+    common
+}
+
+fn write_zip(out: &mut impl Write, entries: &[ArchiveEntry]) -> io::Result<()> {
+    let too_large = || invalid_input("the workspace is too large for a zip archive".to_string());
+    let count = u16::try_from(entries.len()).map_err(|_| too_large())?;
+    let mut central_directory = Vec::new();
+    let mut offset = 0_u32;
+
+    for entry in entries {
+        let name = entry.name.as_bytes();
+        let name_len = u16::try_from(name.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(entry.size).map_err(|_| too_large())?;
+
+        // The CRC-32 is only known once the contents have been copied, so the local
+        // header leaves it empty and a data descriptor after the contents records it.
+        out.write_all(&0x0403_4b50_u32.to_le_bytes())?;
+        out.write_all(&zip_common_fields(entry, 0, 0, name_len))?;
+        out.write_all(&0_u16.to_le_bytes())?;
+        out.write_all(name)?;
+
+        let mut writer = Crc32Writer {
+            inner: out,
+            crc: !0,
+        };
+        copy_entry(entry, &mut writer)?;
+        let crc = !writer.crc;
+
+        out.write_all(&0x0807_4b50_u32.to_le_bytes())?;
+        out.write_all(&crc.to_le_bytes())?;
+        out.write_all(&size.to_le_bytes())?;
+        out.write_all(&size.to_le_bytes())?;
+
+        central_directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+        central_directory.extend_from_slice(&20_u16.to_le_bytes());
+        central_directory.extend_from_slice(&zip_common_fields(entry, crc, size, name_len));
+        // Extra field and comment lengths, disk number and file attributes.
+        central_directory.extend_from_slice(&[0; 12]);
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name);
+
+        offset = u32::try_from(30 + name.len() as u64 + entry.size + 16)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or_else(too_large)?;
+    }
+
+    let central_directory_len = u32::try_from(central_directory.len()).map_err(|_| too_large())?;
+    out.write_all(&central_directory)?;
+
+    out.write_all(&0x0605_4b50_u32.to_le_bytes())?;
+    out.write_all(&[0; 4])?;
+    out.write_all(&count.to_le_bytes())?;
+    out.write_all(&count.to_le_bytes())?;
+    out.write_all(&central_directory_len.to_le_bytes())?;
+    out.write_all(&offset.to_le_bytes())?;
+    out.write_all(&0_u16.to_le_bytes())
+}
+
+impl Workspace {
+    /// Writes every file in the workspace into an archive, e.g. for backups, using paths
+    /// relative to the workspace root. Like [`Workspace::files`], hidden files and files
+    /// excluded by a `.norgignore` are skipped; unlike it, attachments are included.
+    /// The archive itself is skipped if it is written into the workspace.
+    ///
+    /// Files are streamed into the archive one at a time rather than read into memory.
+    /// The archive is written to a temporary file next to `out` and renamed into place
+    /// once complete, so an existing archive is only replaced by a finished one.
+    ///
+    /// * `out`: The path of the archive to create. An existing file is replaced.
+    /// * `format`: The format of the archive.
+    pub fn archive_to(&self, out: &Path, format: ArchiveFormat) -> io::Result<()> {
+        self.ensure_std_filesystem("archiving")?;
+        let root = std::path::absolute(&self.path)?;
+        let out_path = std::path::absolute(out)?;
+        let temp = temp_path(&out_path);
+        let mut paths: Vec<PathBuf> = Vec::new();
+
+        self.walk_files(|path| {
+            if path != out_path && path != temp {
+                paths.push(path);
+            }
+
+            ControlFlow::Continue(())
+        })?;
+        paths.sort();
+
+        let entries = paths
+            .into_iter()
+            .map(|path| {
+                let metadata = fs::metadata(&path)?;
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |elapsed| elapsed.as_secs());
+
+                Ok(ArchiveEntry {
+                    name: entry_name(path.strip_prefix(&root).unwrap_or(&path))?,
+                    path,
+                    size: metadata.len(),
+                    modified,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let result = fs::File::create_new(&temp)
+            .and_then(|file| {
+                let mut file = BufWriter::new(file);
+                match format {
+                    ArchiveFormat::Tar => write_tar(&mut file, &entries)?,
+                    ArchiveFormat::Zip => write_zip(&mut file, &entries)?,
+                }
+
+                file.into_inner()
+                    .map_err(io::IntoInnerError::into_error)?
+                    .sync_all()
+            })
+            .and_then(|()| fs::rename(&temp, &out_path));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_workspace(dir: &Path) -> Workspace {
+        fs::create_dir_all(dir.join("notes/images")).unwrap();
+        fs::write(dir.join("notes/index.norg"), "* Index").unwrap();
+        fs::write(dir.join("notes/images/diagram.png"), [0; 600]).unwrap();
+        fs::write(dir.join("notes/.hidden.norg"), "").unwrap();
+
+        Workspace::with_path("notes", dir.join("notes"))
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_archive_to_tar() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = example_workspace(dir.path());
+        let out = dir.path().join("backup.tar");
+
+        workspace
+            .archive_to(&out, ArchiveFormat::Tar)
+            .expect("Unable to archive the workspace");
+        let archive = fs::read(&out).unwrap();
+
+        // Each file takes a header block plus its contents padded to whole blocks.
+        assert_eq!(archive.len(), 512 + 1024 + 512 + 512 + 1024);
+        assert!(archive.starts_with(b"images/diagram.png\0"));
+        assert_eq!(&archive[257..263], b"ustar\0");
+        assert!(archive[1536..].starts_with(b"index.norg\0"));
+        assert!(archive[2048..].starts_with(b"* Index"));
+    }
+
+    #[test]
+    fn test_archive_to_zip() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = example_workspace(dir.path());
+        let out = workspace.path.join("backup.zip");
+
+        workspace
+            .archive_to(&out, ArchiveFormat::Zip)
+            .expect("Unable to archive the workspace");
+        let archive = fs::read(&out).unwrap();
+
+        assert!(archive.starts_with(&0x0403_4b50_u32.to_le_bytes()));
+        assert_eq!(&archive[30..48], b"images/diagram.png");
+
+        let end = &archive[archive.len() - 22..];
+        assert!(end.starts_with(&0x0605_4b50_u32.to_le_bytes()));
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+
+        // The CRC-32 and sizes follow the contents in a data descriptor.
+        let descriptor = 30 + b"images/diagram.png".len() + 600;
+        assert_eq!(
+            &archive[descriptor..descriptor + 4],
+            &0x0807_4b50_u32.to_le_bytes()
+        );
+        assert_eq!(
+            &archive[descriptor + 4..descriptor + 8],
+            &crc32(&[0; 600]).to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_archive_to_replaces_existing() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = example_workspace(dir.path());
+        let out = dir.path().join("backup.tar");
+        fs::write(&out, "an older backup").unwrap();
+
+        workspace
+            .archive_to(&out, ArchiveFormat::Tar)
+            .expect("Unable to archive the workspace");
+
+        assert!(fs::read(&out).unwrap().starts_with(b"images/diagram.png\0"));
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names.len(), 2, "unexpected files: {names:?}");
+    }
+}
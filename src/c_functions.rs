@@ -11,6 +11,12 @@ pub struct FileList {
     pub _capacity: usize,
 }
 
+/// Creates a new workspace from a name and a path.
+/// The returned workspace must be freed with [`destroy_workspace`].
+///
+/// # Safety
+///
+/// `name` and `path` must be valid, non-null, nul-terminated C strings.
 #[no_mangle]
 pub unsafe extern "C" fn create_workspace(
     name: *const c_char,
@@ -23,14 +29,21 @@ pub unsafe extern "C" fn create_workspace(
         std::ffi::CStr::from_ptr(name),
         std::ffi::CStr::from_ptr(path),
     );
-    let workspace = Workspace {
-        name: name.to_string_lossy().to_string(),
-        path: path.to_string_lossy().to_string().into(),
-    };
+    let workspace = Workspace::with_path(
+        name.to_string_lossy().to_string(),
+        path.to_string_lossy().to_string(),
+    );
 
     Box::into_raw(workspace.into())
 }
 
+/// Returns a list of the absolute paths of all `.norg` files in the workspace.
+/// The returned list must be freed with [`destroy_files`].
+/// Returns `null` if the workspace could not be traversed.
+///
+/// # Safety
+///
+/// `workspace` must be a valid, non-null pointer obtained from [`create_workspace`].
 #[no_mangle]
 pub unsafe extern "C" fn workspace_files(workspace: *const Workspace) -> *mut FileList {
     assert!(
@@ -38,9 +51,12 @@ pub unsafe extern "C" fn workspace_files(workspace: *const Workspace) -> *mut Fi
         "Parameter `workspace` must not be `null`!"
     );
 
+    let Ok(files) = (*workspace).files_absolute() else {
+        return std::ptr::null_mut();
+    };
+
     let files = ManuallyDrop::new(
-        (*workspace)
-            .files()
+        files
             .into_iter()
             .map(|path| {
                 CString::new(path.to_string_lossy().into_owned())
@@ -59,6 +75,12 @@ pub unsafe extern "C" fn workspace_files(workspace: *const Workspace) -> *mut Fi
     Box::into_raw(file_list.into())
 }
 
+/// Frees a file list returned by [`workspace_files`].
+///
+/// # Safety
+///
+/// `file_list` must either be `null` or a pointer obtained from [`workspace_files`]
+/// that has not already been freed.
 #[no_mangle]
 pub unsafe extern "C" fn destroy_files(file_list: *mut FileList) {
     if file_list.is_null() {
@@ -78,6 +100,12 @@ pub unsafe extern "C" fn destroy_files(file_list: *mut FileList) {
     }
 }
 
+/// Frees a workspace returned by [`create_workspace`].
+///
+/// # Safety
+///
+/// `workspace` must either be `null` or a pointer obtained from [`create_workspace`]
+/// that has not already been freed.
 #[no_mangle]
 pub unsafe extern "C" fn destroy_workspace(workspace: *mut Workspace) {
     if workspace.is_null() {
@@ -115,6 +143,8 @@ mod tests {
             let workspace = create_workspace(name, path);
 
             let files = workspace_files(workspace);
+            assert!(!files.is_null());
+            assert_eq!((*files).length, 2);
 
             destroy_files(files);
             destroy_workspace(workspace);
@@ -0,0 +1,624 @@
+use crate::expand::ExpandError;
+use crate::workspace::{
+    InvalidWorkspace, NameError, Workspace, WorkspaceError, WorkspaceManager, WorkspaceNotFound,
+};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::{env, fmt, io};
+
+/// An error that occurs while loading a workspace manager from a configuration source
+/// or saving it into one.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration could not be read.
+    Io(io::Error),
+    /// The configuration is malformed.
+    Parse(String),
+    /// A workspace path could not be expanded.
+    Expand(ExpandError),
+    /// A workspace name is invalid.
+    Name(NameError),
+    /// The default workspace is not one of the configured workspaces.
+    MissingDefault(WorkspaceNotFound),
+    /// The environment variables configuring the workspaces are missing or invalid.
+    Env(String),
+    /// The workspace manager could not be saved, e.g. because a workspace path
+    /// is not valid unicode.
+    Serialize(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "unable to read config: {err}"),
+            ConfigError::Parse(err) => write!(f, "unable to parse config: {err}"),
+            ConfigError::Expand(err) => write!(f, "invalid workspace path: {err}"),
+            ConfigError::Name(err) => write!(f, "invalid workspace name: {err}"),
+            ConfigError::MissingDefault(err) => write!(f, "invalid default workspace: {err}"),
+            ConfigError::Env(err) => write!(f, "invalid environment: {err}"),
+            ConfigError::Serialize(err) => write!(f, "unable to save config: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Parse(_) => None,
+            ConfigError::Expand(err) => Some(err),
+            ConfigError::Name(err) => Some(err),
+            ConfigError::MissingDefault(err) => Some(err),
+            ConfigError::Env(_) => None,
+            ConfigError::Serialize(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<ExpandError> for ConfigError {
+    fn from(err: ExpandError) -> Self {
+        ConfigError::Expand(err)
+    }
+}
+
+impl From<NameError> for ConfigError {
+    fn from(err: NameError) -> Self {
+        ConfigError::Name(err)
+    }
+}
+
+impl From<InvalidWorkspace> for ConfigError {
+    fn from(err: InvalidWorkspace) -> Self {
+        match err {
+            InvalidWorkspace::Name(err) => ConfigError::Name(err),
+            InvalidWorkspace::Path(err) => ConfigError::Expand(err),
+        }
+    }
+}
+
+impl From<WorkspaceNotFound> for ConfigError {
+    fn from(err: WorkspaceNotFound) -> Self {
+        ConfigError::MissingDefault(err)
+    }
+}
+
+impl From<WorkspaceError> for ConfigError {
+    fn from(err: WorkspaceError) -> Self {
+        match err {
+            WorkspaceError::NotFound(err) => ConfigError::MissingDefault(err),
+            WorkspaceError::InvalidName(err) => ConfigError::Name(err),
+            WorkspaceError::InvalidPath(err) => ConfigError::Expand(err),
+            WorkspaceError::Io(err) => ConfigError::Io(err),
+            // E.g. two names that are the same once surrounding whitespace is removed.
+            err => ConfigError::Parse(err.to_string()),
+        }
+    }
+}
+
+impl WorkspaceManager {
+    /// Loads a workspace manager from environment variables starting with `prefix`.
+    /// With the prefix `NEORG_WS_`, `NEORG_WS_notes=~/notes` defines a workspace named `notes`
+    /// and `NEORG_WS_DEFAULT=notes` sets it as the current workspace, so no workspace
+    /// can be named `DEFAULT`. Without a default there is no current workspace.
+    ///
+    /// Workspace paths are expanded. An `Env` error is returned if no workspace is defined
+    /// or if a variable is empty or not valid unicode.
+    ///
+    /// * `prefix`: The prefix of the environment variables, e.g. `NEORG_WS_`.
+    pub fn from_env(prefix: &str) -> Result<WorkspaceManager, ConfigError> {
+        let mut default = None;
+        let mut workspaces = Vec::new();
+
+        for (key, value) in env::vars_os() {
+            let Some(name) = key.to_str().and_then(|key| key.strip_prefix(prefix)) else {
+                continue;
+            };
+            let value = value
+                .into_string()
+                .map_err(|_| ConfigError::Env(format!("{prefix}{name} is not valid unicode")))?;
+
+            if name.is_empty() {
+                continue;
+            } else if value.is_empty() {
+                return Err(ConfigError::Env(format!("{prefix}{name} is empty")));
+            } else if name == "DEFAULT" {
+                default = Some(value);
+            } else {
+                workspaces.push(Workspace::new(name.to_string(), &value)?);
+            }
+        }
+
+        if workspaces.is_empty() {
+            return Err(ConfigError::Env(format!(
+                "no workspaces are defined through {prefix}* variables"
+            )));
+        }
+
+        match default {
+            Some(default) => Ok(WorkspaceManager::new(workspaces, &default)?),
+            None => Ok(WorkspaceManager::without_current_workspace(workspaces)?),
+        }
+    }
+}
+
+/// The on-disk layout of a workspace configuration: the names of the default and
+/// fallback workspaces, a table mapping aliases to workspace names and a table mapping
+/// workspace names to their settings. Without a default there is no current workspace.
+#[cfg(feature = "serde")]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fallback: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    aliases: BTreeMap<String, String>,
+    workspaces: BTreeMap<String, WorkspaceConfig>,
+}
+
+/// The settings of a configured workspace: either just its path, or a table with
+/// the path and the optional settings of the workspace.
+#[cfg(feature = "serde")]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum WorkspaceConfig {
+    Path(String),
+    Table {
+        path: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        extensions: Vec<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        read_only: bool,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        metadata: BTreeMap<String, String>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl WorkspaceConfig {
+    fn into_workspace(
+        self,
+        name: String,
+        base: Option<&std::path::Path>,
+    ) -> Result<Workspace, InvalidWorkspace> {
+        let (path, extensions, read_only, metadata) = match self {
+            WorkspaceConfig::Path(path) => (path, Vec::new(), false, BTreeMap::new()),
+            WorkspaceConfig::Table {
+                path,
+                extensions,
+                read_only,
+                metadata,
+            } => (path, extensions, read_only, metadata),
+        };
+
+        let mut workspace = Workspace::new(name, &path)?;
+        if let Some(base) = base.filter(|_| !path.starts_with(['~', '$'])) {
+            workspace = workspace.resolve_relative_to(base);
+        }
+        workspace.extensions = extensions;
+        workspace.read_only = read_only;
+        workspace.metadata = metadata.into_iter().collect();
+
+        Ok(workspace)
+    }
+
+    fn from_workspace(workspace: &Workspace) -> Result<WorkspaceConfig, ConfigError> {
+        let path = workspace.path.to_str().ok_or_else(|| {
+            ConfigError::Serialize(format!(
+                "the path of workspace \"{}\" is not valid unicode",
+                workspace.name
+            ))
+        })?;
+
+        if workspace.extensions.is_empty() && !workspace.read_only && workspace.metadata.is_empty()
+        {
+            return Ok(WorkspaceConfig::Path(path.to_string()));
+        }
+
+        Ok(WorkspaceConfig::Table {
+            path: path.to_string(),
+            extensions: workspace.extensions.clone(),
+            read_only: workspace.read_only,
+            metadata: workspace.metadata.clone().into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Config {
+    fn into_workspace_manager(
+        self,
+        base: Option<&std::path::Path>,
+    ) -> Result<WorkspaceManager, ConfigError> {
+        let workspaces = self
+            .workspaces
+            .into_iter()
+            .map(|(name, workspace)| workspace.into_workspace(name, base))
+            .collect::<Result<Vec<_>, InvalidWorkspace>>()?;
+
+        let mut workspace_manager = match self.default {
+            Some(default) => WorkspaceManager::new(workspaces, &default)?,
+            None => WorkspaceManager::without_current_workspace(workspaces)?,
+        };
+        if let Some(fallback) = self.fallback {
+            workspace_manager
+                .set_fallback_workspace(fallback)
+                .map_err(|err| ConfigError::Parse(format!("invalid fallback workspace: {err}")))?;
+        }
+        for (alias, name) in self.aliases {
+            workspace_manager.add_alias(alias, &name)?;
+        }
+
+        Ok(workspace_manager)
+    }
+
+    fn from_workspace_manager(workspace_manager: &WorkspaceManager) -> Result<Config, ConfigError> {
+        Ok(Config {
+            default: workspace_manager
+                .default_workspace
+                .clone()
+                .or_else(|| workspace_manager.current_workspace.clone()),
+            fallback: workspace_manager.fallback_workspace.clone(),
+            aliases: workspace_manager.aliases.clone().into_iter().collect(),
+            workspaces: workspace_manager
+                .workspaces
+                .values()
+                .map(|w| Ok((w.name.clone(), WorkspaceConfig::from_workspace(w)?)))
+                .collect::<Result<_, ConfigError>>()?,
+        })
+    }
+}
+
+#[cfg(feature = "toml")]
+impl WorkspaceManager {
+    /// Loads a workspace manager from a TOML file of the form:
+    ///
+    /// ```toml
+    /// default = "notes"
+    ///
+    /// [workspaces]
+    /// notes = "~/notes"
+    /// work = "$HOME/work/notes"
+    /// ```
+    ///
+    /// A workspace can also be a table with its `path` and optional `extensions`,
+    /// `read_only` and `metadata` settings, and the optional top-level `fallback` and
+    /// `[aliases]` keys set the fallback workspace and map aliases to workspace names:
+    ///
+    /// ```toml
+    /// default = "notes"
+    /// fallback = "notes"
+    ///
+    /// [aliases]
+    /// w = "work"
+    ///
+    /// [workspaces]
+    /// notes = "~/notes"
+    /// work = { path = "~/work", extensions = ["norg", "md"], read_only = true }
+    /// ```
+    ///
+    /// Workspace paths are expanded and the default workspace also becomes the current workspace.
+    /// Relative workspace paths are relative to the directory containing the file,
+    /// see [`WorkspaceManager::from_toml_str_relative_to`].
+    ///
+    /// * `path`: The path to the TOML file.
+    pub fn from_toml_path(path: &std::path::Path) -> Result<WorkspaceManager, ConfigError> {
+        let toml = std::fs::read_to_string(path)?;
+
+        match path.parent() {
+            Some(base) => WorkspaceManager::from_toml_str_relative_to(&toml, base),
+            None => WorkspaceManager::from_toml_str(&toml),
+        }
+    }
+
+    /// Loads a workspace manager from a TOML string.
+    /// See [`WorkspaceManager::from_toml_path`] for the expected layout.
+    ///
+    /// * `toml`: The TOML source.
+    pub fn from_toml_str(toml: &str) -> Result<WorkspaceManager, ConfigError> {
+        toml::from_str::<Config>(toml)
+            .map_err(|err| ConfigError::Parse(err.to_string()))?
+            .into_workspace_manager(None)
+    }
+
+    /// Like [`WorkspaceManager::from_toml_str`], but resolves relative workspace paths
+    /// against a base directory, e.g. the directory containing the configuration file.
+    /// Paths starting with `~` or `$` are expanded as usual.
+    ///
+    /// * `toml`: The TOML source.
+    /// * `base`: The directory relative workspace paths are relative to.
+    pub fn from_toml_str_relative_to(
+        toml: &str,
+        base: &std::path::Path,
+    ) -> Result<WorkspaceManager, ConfigError> {
+        toml::from_str::<Config>(toml)
+            .map_err(|err| ConfigError::Parse(err.to_string()))?
+            .into_workspace_manager(Some(base))
+    }
+
+    /// Serializes the workspace manager into the layout read by
+    /// [`WorkspaceManager::from_toml_path`], using the default workspace as the default,
+    /// or the current workspace if there is no default.
+    /// Fails with a `Serialize` error if a workspace path is not valid unicode.
+    pub fn to_toml_string(&self) -> Result<String, ConfigError> {
+        // Going through a value emits plain keys before tables, as TOML requires.
+        toml::Value::try_from(Config::from_workspace_manager(self)?)
+            .and_then(|config| toml::to_string(&config))
+            .map_err(|err| ConfigError::Serialize(err.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl WorkspaceManager {
+    /// Loads a workspace manager from a JSON string of the form:
+    ///
+    /// ```json
+    /// {
+    ///   "default": "notes",
+    ///   "workspaces": {
+    ///     "notes": "~/notes",
+    ///     "work": "$HOME/work/notes"
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Like [`WorkspaceManager::from_toml_str`], workspace paths are expanded and the default
+    /// workspace, which is optional, is set as the current workspace.
+    ///
+    /// * `json`: The JSON source.
+    pub fn from_json(json: &str) -> Result<WorkspaceManager, ConfigError> {
+        serde_json::from_str::<Config>(json)
+            .map_err(|err| ConfigError::Parse(err.to_string()))?
+            .into_workspace_manager(None)
+    }
+
+    /// Like [`WorkspaceManager::from_json`], but resolves relative workspace paths
+    /// against a base directory, see [`WorkspaceManager::from_toml_str_relative_to`].
+    ///
+    /// * `json`: The JSON source.
+    /// * `base`: The directory relative workspace paths are relative to.
+    pub fn from_json_relative_to(
+        json: &str,
+        base: &std::path::Path,
+    ) -> Result<WorkspaceManager, ConfigError> {
+        serde_json::from_str::<Config>(json)
+            .map_err(|err| ConfigError::Parse(err.to_string()))?
+            .into_workspace_manager(Some(base))
+    }
+
+    /// Serializes the workspace manager into the pretty-printed layout read by
+    /// [`WorkspaceManager::from_json`], using the default workspace as the default,
+    /// or the current workspace if there is no default.
+    /// Fails with a `Serialize` error if a workspace path is not valid unicode.
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        serde_json::to_string_pretty(&Config::from_workspace_manager(self)?)
+            .map_err(|err| ConfigError::Serialize(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_env() {
+        // Every test uses its own prefix, as the environment is shared between threads.
+        env::set_var("DIRMAN_TEST_ENV_notes", "/home/user/notes");
+        env::set_var("DIRMAN_TEST_ENV_work", "/home/user/work");
+        env::set_var("DIRMAN_TEST_ENV_DEFAULT", "work");
+
+        let workspace_manager =
+            WorkspaceManager::from_env("DIRMAN_TEST_ENV_").expect("Unable to load the config");
+        assert_eq!(workspace_manager.names_sorted(), vec!["notes", "work"]);
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "work"
+        );
+
+        env::set_var("DIRMAN_TEST_ENV_DEFAULT", "missing");
+        assert!(matches!(
+            WorkspaceManager::from_env("DIRMAN_TEST_ENV_"),
+            Err(ConfigError::MissingDefault(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_env_errors() {
+        assert!(matches!(
+            WorkspaceManager::from_env("DIRMAN_TEST_UNSET_"),
+            Err(ConfigError::Env(_))
+        ));
+
+        env::set_var("DIRMAN_TEST_EMPTY_notes", "");
+        assert!(matches!(
+            WorkspaceManager::from_env("DIRMAN_TEST_EMPTY_"),
+            Err(ConfigError::Env(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip() {
+        let toml = r#"
+            default = "notes"
+
+            [workspaces]
+            notes = "/home/user/notes"
+            work = "/home/user/work"
+        "#;
+
+        let mut workspace_manager =
+            WorkspaceManager::from_toml_str(toml).expect("Unable to load the config");
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "notes"
+        );
+        assert_eq!(workspace_manager.workspaces.len(), 2);
+
+        // Switching workspaces doesn't change the configured default.
+        workspace_manager.set_current_workspace("work").unwrap();
+
+        let reloaded =
+            WorkspaceManager::from_toml_str(&workspace_manager.to_toml_string().unwrap())
+                .expect("Unable to reload the config");
+        assert_eq!(reloaded.get_current_workspace().unwrap().name, "notes");
+        assert_eq!(
+            reloaded.get_workspace("work").unwrap().path,
+            std::path::PathBuf::from("/home/user/work")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let json = r#"{
+            "default": "notes",
+            "workspaces": { "notes": "/home/user/notes", "work": "/home/user/work" }
+        }"#;
+
+        let workspace_manager =
+            WorkspaceManager::from_json(json).expect("Unable to load the config");
+        let reloaded = WorkspaceManager::from_json(&workspace_manager.to_json().unwrap())
+            .expect("Unable to reload the config");
+        assert_eq!(reloaded.names_sorted(), vec!["notes", "work"]);
+        assert_eq!(reloaded.get_current_workspace().unwrap().name, "notes");
+        assert!(workspace_manager.to_json().unwrap().contains('\n'));
+
+        assert!(matches!(
+            WorkspaceManager::from_json(r#"{ "default": "missing", "workspaces": {} }"#),
+            Err(ConfigError::MissingDefault(_))
+        ));
+        assert!(matches!(
+            WorkspaceManager::from_json("{"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip_settings() {
+        let mut workspace_manager = WorkspaceManager::new(
+            vec![
+                Workspace::with_path("archive", "/home/user/archive")
+                    .with_read_only(true)
+                    .with_extensions(vec!["norg".to_string(), "md".to_string()])
+                    .with_metadata("owner", "me"),
+                Workspace::with_path("notes", "/home/user/notes"),
+            ],
+            "notes",
+        )
+        .unwrap();
+        workspace_manager.set_fallback_workspace("archive").unwrap();
+        workspace_manager.add_alias("a", "archive").unwrap();
+
+        let toml = workspace_manager
+            .to_toml_string()
+            .expect("Unable to save the config");
+        let reloaded = WorkspaceManager::from_toml_str(&toml).expect("Unable to reload the config");
+        let archive = reloaded.get_workspace("archive").unwrap();
+        assert!(archive.read_only);
+        assert_eq!(archive.extensions, vec!["norg", "md"]);
+        assert_eq!(
+            archive.metadata.get("owner").map(String::as_str),
+            Some("me")
+        );
+        assert_eq!(reloaded.get_workspace("a").unwrap().name, "archive");
+        assert_eq!(reloaded.get_fallback_workspace().unwrap().name, "archive");
+        assert_eq!(reloaded.get_current_workspace().unwrap().name, "notes");
+
+        let json = workspace_manager
+            .to_json()
+            .expect("Unable to save the config");
+        let reloaded = WorkspaceManager::from_json(&json).expect("Unable to reload the config");
+        assert!(reloaded.get_workspace("archive").unwrap().read_only);
+        assert_eq!(reloaded.get_workspace("a").unwrap().name, "archive");
+    }
+
+    #[cfg(all(feature = "toml", unix))]
+    #[test]
+    fn test_save_non_unicode_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::OsStr::from_bytes(b"/home/user/\xff");
+        let workspace_manager =
+            WorkspaceManager::without_current_workspace(vec![Workspace::with_path("notes", path)])
+                .unwrap();
+        assert!(matches!(
+            workspace_manager.to_toml_string(),
+            Err(ConfigError::Serialize(_))
+        ));
+        assert!(matches!(
+            workspace_manager.to_json(),
+            Err(ConfigError::Serialize(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_relative_to() {
+        let toml = r#"
+            [workspaces]
+            notes = "notes"
+            work = "/work"
+        "#;
+
+        let workspace_manager =
+            WorkspaceManager::from_toml_str_relative_to(toml, std::path::Path::new("/config"))
+                .expect("Unable to load the config");
+        assert_eq!(
+            workspace_manager.get_workspace("notes").unwrap().path,
+            std::path::PathBuf::from("/config/notes")
+        );
+        assert_eq!(
+            workspace_manager.get_workspace("work").unwrap().path,
+            std::path::PathBuf::from("/work")
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_path_relative_to_config() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let config_dir = dir.path().join("config");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("dirman.toml"),
+            "[workspaces]\nnotes = \"notes\"\nwork = \"../work\"\n",
+        )
+        .unwrap();
+
+        let workspace_manager = WorkspaceManager::from_toml_path(&config_dir.join("dirman.toml"))
+            .expect("Unable to load the config");
+        assert_eq!(
+            workspace_manager.get_workspace("notes").unwrap().path,
+            config_dir.join("notes")
+        );
+        assert_eq!(
+            workspace_manager.get_workspace("work").unwrap().path,
+            config_dir.join("../work")
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_errors() {
+        assert!(matches!(
+            WorkspaceManager::from_toml_str("default = "),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            WorkspaceManager::from_toml_str("default = \"missing\"\n[workspaces]\n"),
+            Err(ConfigError::MissingDefault(_))
+        ));
+        assert!(matches!(
+            WorkspaceManager::from_toml_path(std::path::Path::new("test/missing.toml")),
+            Err(ConfigError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::workspace::Workspace;
+use std::{io, path::Path};
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> io::Result<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` is only read after
+    // `statvfs` succeeded and initialized it.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        stat.assume_init()
+    };
+
+    // The field types differ between platforms, so they are widened explicitly.
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn available_space(path: &Path) -> io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_bytes_available_to_caller: *mut u64,
+            total_bytes: *mut u64,
+            total_free_bytes: *mut u64,
+        ) -> i32;
+    }
+
+    // Any directory on the volume is accepted, so a missing path would silently report
+    // the space of its volume rather than fail.
+    std::fs::metadata(path)?;
+    let path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut available = 0;
+
+    // SAFETY: `path` is a valid NUL-terminated wide string and the unused outputs are null.
+    if unsafe {
+        GetDiskFreeSpaceExW(
+            path.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn available_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "querying the available disk space is not supported on this platform",
+    ))
+}
+
+impl Workspace {
+    /// Returns the number of bytes available to the current user on the filesystem
+    /// containing the workspace, e.g. to warn before a large import.
+    /// Fails with a `NotFound` error if the workspace directory doesn't exist.
+    pub fn available_space(&self) -> io::Result<u64> {
+        self.ensure_std_filesystem("querying the available disk space")?;
+        available_space(&self.expanded_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_space() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+        assert!(workspace.available_space().is_ok());
+
+        let missing = Workspace::with_path("missing", dir.path().join("missing"));
+        assert_eq!(
+            missing.available_space().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use std::{collections::HashMap, env, fmt, path::PathBuf};
+
+/// An error that occurs while expanding a raw workspace path.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExpandError {
+    /// The current user's home directory could not be determined.
+    HomeNotFound,
+    /// The user referenced by a `~user` prefix does not exist.
+    UnknownUser(String),
+    /// An environment variable referenced by `$VAR` or `${VAR}` is not set.
+    VarNotFound(String),
+    /// A `${` was opened but never closed.
+    UnterminatedVar,
+    /// A `{name}` placeholder has no value.
+    UnknownPlaceholder(String),
+    /// A `{` was opened but never closed.
+    UnterminatedPlaceholder,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::HomeNotFound => write!(f, "unable to determine the home directory"),
+            ExpandError::UnknownUser(user) => write!(f, "unknown user \"{user}\""),
+            ExpandError::VarNotFound(var) => {
+                write!(f, "environment variable \"{var}\" is not set")
+            }
+            ExpandError::UnterminatedVar => write!(f, "unterminated \"${{\" in path"),
+            ExpandError::UnknownPlaceholder(name) => {
+                write!(f, "no value for path placeholder \"{name}\"")
+            }
+            ExpandError::UnterminatedPlaceholder => write!(f, "unterminated \"{{\" in path"),
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+/// Expands a leading `~` or `~user` and all `$VAR`/`${VAR}` occurrences in a raw path.
+/// Paths containing neither are returned unchanged.
+///
+/// * `raw`: The unexpanded path, e.g. `~/notes` or `$XDG_DATA_HOME/neorg`.
+pub fn expand_path(raw: &str) -> Result<PathBuf, ExpandError> {
+    expand_path_with_home(raw, home_dir())
+}
+
+/// Like [`expand_path`], but with the given home directory of the current user
+/// rather than the one from the environment.
+fn expand_path_with_home(raw: &str, home: Option<PathBuf>) -> Result<PathBuf, ExpandError> {
+    let Some(rest) = raw.strip_prefix('~') else {
+        return expand_vars(raw).map(PathBuf::from);
+    };
+
+    let (user, rest) = match rest.find(is_separator) {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+
+    let home = if user.is_empty() {
+        home.ok_or(ExpandError::HomeNotFound)?
+    } else {
+        user_home_dir(user).ok_or_else(|| ExpandError::UnknownUser(user.to_string()))?
+    };
+
+    let rest = expand_vars(rest.trim_start_matches(is_separator))?;
+
+    Ok(if rest.is_empty() {
+        home
+    } else {
+        home.join(rest)
+    })
+}
+
+/// Substitutes every `{name}` placeholder in a path template with its value.
+/// Unlike `${VAR}`, which refers to an environment variable and is left untouched,
+/// placeholders without a value are an error.
+///
+/// * `template`: The path template, e.g. `{home}/work/{project}/notes`.
+/// * `vars`: The values of the placeholders.
+pub fn expand_placeholders(
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, ExpandError> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let (before, placeholder) = rest.split_at(start);
+        result.push_str(before);
+
+        if before.ends_with('$') {
+            result.push('{');
+            rest = &placeholder[1..];
+            continue;
+        }
+
+        let end = placeholder
+            .find('}')
+            .ok_or(ExpandError::UnterminatedPlaceholder)?;
+        let name = &placeholder[1..end];
+        let value = vars
+            .get(name)
+            .ok_or_else(|| ExpandError::UnknownPlaceholder(name.to_string()))?;
+
+        result.push_str(value);
+        rest = &placeholder[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn expand_vars(path: &str) -> Result<String, ExpandError> {
+    let mut result = String::with_capacity(path.len());
+    let mut chars = path.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        let name = match chars.peek() {
+            Some((_, '{')) => {
+                let start = index + 2;
+                let end = path[start..]
+                    .find('}')
+                    .ok_or(ExpandError::UnterminatedVar)?
+                    + start;
+
+                while chars.next_if(|(i, _)| *i <= end).is_some() {}
+
+                &path[start..end]
+            }
+            Some((start, c)) if is_var_char(*c) => {
+                let start = *start;
+                let mut end = start;
+
+                while let Some((i, c)) = chars.next_if(|(_, c)| is_var_char(*c)) {
+                    end = i + c.len_utf8();
+                }
+
+                &path[start..end]
+            }
+            _ => {
+                result.push(c);
+                continue;
+            }
+        };
+
+        let value = env::var(name).map_err(|_| ExpandError::VarNotFound(name.to_string()))?;
+        result.push_str(&value);
+    }
+
+    Ok(result)
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_separator(c: char) -> bool {
+    std::path::is_separator(c)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+    env::var_os(var)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    use std::ffi::{CStr, CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+
+    // Going through the C library also finds users from NSS sources like LDAP,
+    // which never show up in `/etc/passwd`.
+    let name = CString::new(user).ok()?;
+    let mut buffer = vec![0; 1024];
+
+    loop {
+        let mut passwd = std::mem::MaybeUninit::<libc::passwd>::uninit();
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: `name` is a valid NUL-terminated string and `buffer` is as long as passed.
+        let err = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                passwd.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+
+        match err {
+            0 if result.is_null() => return None,
+            0 => {
+                // SAFETY: On success `result` points to `passwd`, whose strings live in
+                // `buffer`, both of which are still alive.
+                let home = unsafe { CStr::from_ptr((*result).pw_dir) };
+                return Some(PathBuf::from(OsStr::from_bytes(home.to_bytes())));
+            }
+            libc::ERANGE if buffer.len() < 1 << 20 => buffer.resize(buffer.len() * 2, 0),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tilde() {
+        let home = PathBuf::from("/home/user");
+        let expand = |raw| expand_path_with_home(raw, Some(home.clone()));
+
+        assert_eq!(expand("~").unwrap(), home);
+        assert_eq!(expand("~/notes").unwrap(), home.join("notes"));
+        assert_eq!(expand("~//notes/").unwrap(), home.join("notes/"));
+        assert_eq!(
+            expand("/some/~/path").unwrap(),
+            PathBuf::from("/some/~/path")
+        );
+        assert_eq!(
+            expand_path_with_home("~/notes", None),
+            Err(ExpandError::HomeNotFound)
+        );
+        assert_eq!(
+            expand_path_with_home("/notes", None).unwrap(),
+            PathBuf::from("/notes")
+        );
+        assert_eq!(
+            expand_path("~this-user-does-not-exist/notes"),
+            Err(ExpandError::UnknownUser(
+                "this-user-does-not-exist".to_string()
+            ))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_tilde_user() {
+        let root_home = user_home_dir("root").expect("`root` must exist to run this test");
+
+        assert_eq!(expand_path("~root/notes").unwrap(), root_home.join("notes"));
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        env::set_var("NEORG_DIRMAN_TEST_VAR", "value");
+        env::remove_var("NEORG_DIRMAN_TEST_UNSET");
+
+        assert_eq!(
+            expand_path("/a/$NEORG_DIRMAN_TEST_VAR/b").unwrap(),
+            PathBuf::from("/a/value/b")
+        );
+        assert_eq!(
+            expand_path("/a/${NEORG_DIRMAN_TEST_VAR}b").unwrap(),
+            PathBuf::from("/a/valueb")
+        );
+        assert_eq!(expand_path("/a/$/b").unwrap(), PathBuf::from("/a/$/b"));
+        assert_eq!(
+            expand_path("$NEORG_DIRMAN_TEST_UNSET/notes"),
+            Err(ExpandError::VarNotFound(
+                "NEORG_DIRMAN_TEST_UNSET".to_string()
+            ))
+        );
+        assert_eq!(expand_path("${NEORG"), Err(ExpandError::UnterminatedVar));
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let vars = HashMap::from([
+            ("home".to_string(), "/home/user".to_string()),
+            ("project".to_string(), "dirman".to_string()),
+        ]);
+
+        assert_eq!(
+            expand_placeholders("{home}/work/{project}/notes", &vars),
+            Ok("/home/user/work/dirman/notes".to_string())
+        );
+        assert_eq!(
+            expand_placeholders("${HOME}/{project}", &vars),
+            Ok("${HOME}/dirman".to_string())
+        );
+        assert_eq!(
+            expand_placeholders("{home}/{missing}", &vars),
+            Err(ExpandError::UnknownPlaceholder("missing".to_string()))
+        );
+        assert_eq!(
+            expand_placeholders("{home", &vars),
+            Err(ExpandError::UnterminatedPlaceholder)
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::workspace::Workspace;
+use crate::workspace_files::{already_exists, move_file, not_found, write_atomically};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// The filesystem operations used to create, change and delete the notes of a workspace.
+/// All paths passed to a filesystem are absolute.
+///
+/// The real filesystem is used by default, see [`Workspace::with_filesystem`] to use
+/// another implementation like [`MemoryFileSystem`].
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Returns true if a file or directory exists at the path.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Returns true if a file (rather than a directory) exists at the path.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Returns true if a directory exists at the path.
+    fn is_dir(&self, path: &Path) -> bool {
+        self.exists(path) && !self.is_file(path)
+    }
+
+    /// Returns the paths of the entries of a directory, sorted.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Creates a directory and all of its parents.
+    /// Succeeds without doing anything if the directory already exists.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns the contents of a file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Replaces the contents of a file atomically, creating it if needed.
+    /// With `create_new` it fails with an `AlreadyExists` error if the file exists.
+    fn write(&self, path: &Path, contents: &[u8], create_new: bool) -> io::Result<()>;
+
+    /// Sets the modification time of a file to now. With `create` a missing file is
+    /// created empty, otherwise it is a `NotFound` error.
+    fn touch(&self, path: &Path, create: bool) -> io::Result<()>;
+
+    /// Removes a file.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Moves a file, creating missing destination directories.
+    /// Fails with an `AlreadyExists` error if the destination already exists.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+}
+
+/// The real filesystem, backed by [`std::fs`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        match fs::create_dir_all(path) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && path.is_dir() => Ok(()),
+            result => result,
+        }
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8], create_new: bool) -> io::Result<()> {
+        write_atomically(path, contents, create_new)
+    }
+
+    fn touch(&self, path: &Path, create: bool) -> io::Result<()> {
+        // Setting the times through an open handle rather than the path works on every
+        // platform, but it requires the file to exist - hence the explicit `create` flag.
+        fs::OpenOptions::new()
+            .write(true)
+            .create(create)
+            .truncate(false)
+            .open(path)?
+            .set_modified(SystemTime::now())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        move_file(from, to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// A filesystem that only exists in memory, e.g. to test note operations without
+/// touching the disk. Clones share the same files.
+///
+/// Paths are taken as-is: they are neither normalized nor resolved, and the root
+/// directory always exists. Modification times are not tracked.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    entries: Arc<Mutex<BTreeMap<PathBuf, Entry>>>,
+}
+
+impl MemoryFileSystem {
+    /// Creates an empty in-memory filesystem.
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Entry>> {
+        // A panic while holding the lock cannot leave the map in an inconsistent state.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fails with a `NotFound` error unless the parent directory of the path exists.
+    fn check_parent(entries: &BTreeMap<PathBuf, Entry>, path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) if parent.parent().is_some() && !parent.as_os_str().is_empty() => {
+                match entries.get(parent) {
+                    Some(Entry::Dir) => Ok(()),
+                    _ => Err(not_found(parent)),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn insert_dirs(entries: &mut BTreeMap<PathBuf, Entry>, path: &Path) -> io::Result<()> {
+        for dir in path.ancestors().filter(|dir| dir.parent().is_some()) {
+            match entries.get(dir) {
+                Some(Entry::File(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotADirectory,
+                        format!("\"{}\" is not a directory", dir.display()),
+                    ))
+                }
+                Some(Entry::Dir) => {}
+                None => {
+                    entries.insert(dir.to_path_buf(), Entry::Dir);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        path.parent().is_none() || self.entries().contains_key(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        matches!(self.entries().get(path), Some(Entry::File(_)))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let entries = self.entries();
+
+        if path.parent().is_some() && entries.get(path) != Some(&Entry::Dir) {
+            return Err(not_found(path));
+        }
+
+        Ok(entries
+            .keys()
+            .filter(|entry| entry.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        MemoryFileSystem::insert_dirs(&mut self.entries(), path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.entries().get(path) {
+            Some(Entry::File(contents)) => Ok(contents.clone()),
+            Some(Entry::Dir) => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("\"{}\" is a directory", path.display()),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8], create_new: bool) -> io::Result<()> {
+        let mut entries = self.entries();
+        MemoryFileSystem::check_parent(&entries, path)?;
+
+        match entries.get(path) {
+            Some(Entry::File(_)) if create_new => Err(already_exists(path)),
+            Some(Entry::Dir) => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("\"{}\" is a directory", path.display()),
+            )),
+            _ => {
+                entries.insert(path.to_path_buf(), Entry::File(contents.to_vec()));
+                Ok(())
+            }
+        }
+    }
+
+    fn touch(&self, path: &Path, create: bool) -> io::Result<()> {
+        let mut entries = self.entries();
+
+        match entries.get(path) {
+            Some(_) => Ok(()),
+            None if create => {
+                MemoryFileSystem::check_parent(&entries, path)?;
+                entries.insert(path.to_path_buf(), Entry::File(Vec::new()));
+                Ok(())
+            }
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut entries = self.entries();
+
+        match entries.get(path) {
+            Some(Entry::File(_)) => {
+                entries.remove(path);
+                Ok(())
+            }
+            Some(Entry::Dir) => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("\"{}\" is a directory", path.display()),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut entries = self.entries();
+
+        if entries.contains_key(to) {
+            return Err(already_exists(to));
+        }
+        if !matches!(entries.get(from), Some(Entry::File(_))) {
+            return Err(not_found(from));
+        }
+        if let Some(parent) = to.parent() {
+            MemoryFileSystem::insert_dirs(&mut entries, parent)?;
+        }
+
+        let file = entries.remove(from).expect("the file was checked to exist");
+        entries.insert(to.to_path_buf(), file);
+
+        Ok(())
+    }
+}
+
+impl Workspace {
+    /// Returns the filesystem the notes of the workspace are read from and written to.
+    pub fn filesystem(&self) -> &dyn FileSystem {
+        match &self.filesystem {
+            Some(filesystem) => filesystem.as_ref(),
+            None => &StdFileSystem,
+        }
+    }
+
+    /// Returns whether both workspaces use the same filesystem, so notes can be renamed
+    /// from one into the other.
+    pub(crate) fn shares_filesystem(&self, other: &Workspace) -> bool {
+        match (&self.filesystem, &other.filesystem) {
+            (None, None) => true,
+            (Some(a), Some(b)) => std::ptr::addr_eq(Arc::as_ptr(a), Arc::as_ptr(b)),
+            _ => false,
+        }
+    }
+
+    /// Sets the filesystem the notes of the workspace are read from and written to.
+    ///
+    /// Listing, searching and changing notes go through the filesystem. Operations that
+    /// need file metadata the trait doesn't provide, like modification times, fail with
+    /// an `Unsupported` error instead of reading the real filesystem.
+    ///
+    /// * `filesystem`: The filesystem to use, e.g. a [`MemoryFileSystem`] in tests.
+    pub fn with_filesystem(mut self, filesystem: Arc<dyn FileSystem>) -> Workspace {
+        self.filesystem = Some(filesystem);
+        self
+    }
+
+    /// Fails with an `Unsupported` error if the workspace uses a filesystem other than
+    /// the real one, for operations that can only be carried out on the real filesystem.
+    ///
+    /// * `operation`: A description of the operation for the error message.
+    pub(crate) fn ensure_std_filesystem(&self, operation: &str) -> io::Result<()> {
+        match self.filesystem {
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{operation} is not supported for workspace \"{}\" with a custom filesystem",
+                    self.name
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_file_system() {
+        let filesystem = MemoryFileSystem::new();
+        let note = Path::new("/notes/projects/todo.norg");
+
+        assert!(filesystem.write(note, b"* Todo", false).is_err());
+        filesystem
+            .create_dir_all(Path::new("/notes/projects"))
+            .unwrap();
+        filesystem.write(note, b"* Todo", false).unwrap();
+        assert!(filesystem.is_file(note));
+        assert!(!filesystem.is_file(Path::new("/notes/projects")));
+        assert_eq!(filesystem.read(note).unwrap(), b"* Todo");
+        assert_eq!(
+            filesystem.write(note, b"", true).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            filesystem.read_dir(Path::new("/notes")).unwrap(),
+            vec![PathBuf::from("/notes/projects")]
+        );
+
+        let moved = Path::new("/notes/archive/todo.norg");
+        filesystem.rename(note, moved).unwrap();
+        assert!(!filesystem.exists(note));
+        assert_eq!(filesystem.read(moved).unwrap(), b"* Todo");
+
+        filesystem.remove_file(moved).unwrap();
+        assert_eq!(
+            filesystem.remove_file(moved).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::workspace::Workspace;
+use std::{
+    fmt, io,
+    ops::ControlFlow,
+    path::{Component, Path, PathBuf},
+};
+
+/// An error that occurs while matching a glob pattern against a workspace.
+#[derive(Debug)]
+pub enum GlobError {
+    /// The pattern is malformed, e.g. contains an unclosed `[`.
+    InvalidPattern(String),
+    /// The pattern is absolute rather than relative to the workspace root.
+    Absolute(String),
+    /// The pattern escapes the workspace root through `..`.
+    EscapesRoot(String),
+    /// The workspace could not be traversed.
+    Io(io::Error),
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobError::InvalidPattern(pattern) => write!(f, "invalid glob pattern \"{pattern}\""),
+            GlobError::Absolute(pattern) => {
+                write!(f, "glob pattern \"{pattern}\" must be relative")
+            }
+            GlobError::EscapesRoot(pattern) => {
+                write!(f, "glob pattern \"{pattern}\" escapes the workspace")
+            }
+            GlobError::Io(err) => write!(f, "unable to traverse workspace: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for GlobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GlobError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GlobError {
+    fn from(err: io::Error) -> Self {
+        GlobError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A literal character.
+    Char(char),
+    /// `?`, matching any single character.
+    AnyChar,
+    /// `*`, matching any sequence of characters.
+    AnyChars,
+    /// `[...]` or `[!...]`, matching a single character in (or not in) a set of ranges.
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`, matching any number of path components.
+    AnyComponents,
+    /// A pattern matching a single path component.
+    Component(Vec<Token>),
+}
+
+/// A compiled glob pattern, matched against relative paths.
+///
+/// Supports `?`, `*` and `[...]` within a single path component and `**` to match
+/// any number of components, with the same semantics as the `glob` crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    /// Compiles a glob pattern.
+    /// Absolute patterns and patterns containing `..` are rejected.
+    ///
+    /// * `pattern`: The glob pattern, e.g. `projects/**/*.norg`.
+    pub fn new(pattern: &str) -> Result<Pattern, GlobError> {
+        let segments = Path::new(pattern)
+            .components()
+            .filter_map(|component| match component {
+                Component::CurDir => None,
+                Component::ParentDir => Some(Err(GlobError::EscapesRoot(pattern.to_string()))),
+                Component::RootDir | Component::Prefix(_) => {
+                    Some(Err(GlobError::Absolute(pattern.to_string())))
+                }
+                Component::Normal(component) => Some(match component.to_str() {
+                    Some("**") => Ok(Segment::AnyComponents),
+                    Some(component) => parse_component(component)
+                        .map(Segment::Component)
+                        .ok_or_else(|| GlobError::InvalidPattern(pattern.to_string())),
+                    None => Err(GlobError::InvalidPattern(pattern.to_string())),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pattern { segments })
+    }
+
+    /// Returns true if the relative path matches the pattern.
+    ///
+    /// * `path`: The path to match, relative to the workspace root.
+    pub fn matches(&self, path: &Path) -> bool {
+        let components: Vec<String> = path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(component) => Some(component.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        matches_segments(&self.segments, &components)
+    }
+}
+
+fn parse_component(component: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = component.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '?' => Token::AnyChar,
+            '*' => {
+                while chars.next_if_eq(&'*').is_some() {}
+                Token::AnyChars
+            }
+            '[' => {
+                let negated = chars.next_if(|c| *c == '!' || *c == '^').is_some();
+                let mut ranges = Vec::new();
+
+                // A `]` directly after the opening bracket is matched literally.
+                if let Some(c) = chars.next_if_eq(&']') {
+                    ranges.push((c, c));
+                }
+
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        start => match chars.next_if_eq(&'-') {
+                            Some(_) if chars.peek().is_some_and(|c| *c != ']') => {
+                                ranges.push((start, chars.next()?));
+                            }
+                            Some(dash) => {
+                                ranges.push((start, start));
+                                ranges.push((dash, dash));
+                            }
+                            None => ranges.push((start, start)),
+                        },
+                    }
+                }
+
+                Token::Class { negated, ranges }
+            }
+            c => Token::Char(c),
+        });
+    }
+
+    Some(tokens)
+}
+
+fn matches_segments(segments: &[Segment], components: &[String]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((Segment::AnyComponents, rest)) => {
+            (0..=components.len()).any(|skip| matches_segments(rest, &components[skip..]))
+        }
+        Some((Segment::Component(tokens), rest)) => match components.split_first() {
+            Some((component, components)) => {
+                let chars: Vec<char> = component.chars().collect();
+                matches_tokens(tokens, &chars) && matches_segments(rest, components)
+            }
+            None => false,
+        },
+    }
+}
+
+fn matches_tokens(tokens: &[Token], chars: &[char]) -> bool {
+    match tokens.split_first() {
+        None => chars.is_empty(),
+        Some((Token::AnyChars, rest)) => {
+            (0..=chars.len()).any(|skip| matches_tokens(rest, &chars[skip..]))
+        }
+        Some((token, rest)) => match chars.split_first() {
+            Some((c, chars)) => {
+                let matched = match token {
+                    Token::Char(expected) => c == expected,
+                    Token::AnyChar => true,
+                    Token::Class { negated, ranges } => {
+                        ranges.iter().any(|(start, end)| (start..=end).contains(&c)) != *negated
+                    }
+                    Token::AnyChars => unreachable!(),
+                };
+
+                matched && matches_tokens(rest, chars)
+            }
+            None => false,
+        },
+    }
+}
+
+impl Workspace {
+    /// Returns all files in the workspace matching a glob pattern, relative to the
+    /// workspace root. The pattern is interpreted relative to the workspace root
+    /// and hidden files are skipped just like in [`Workspace::files`].
+    ///
+    /// * `pattern`: The glob pattern, e.g. `projects/**/*.norg`.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<PathBuf>, GlobError> {
+        let pattern = Pattern::new(pattern)?;
+        let root = std::path::absolute(&self.path)?;
+        let mut files = Vec::new();
+
+        self.walk_files(|path| {
+            if let Ok(relative) = path.strip_prefix(&root) {
+                if pattern.matches(relative) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        let matches = |pattern: &str, path: &str| {
+            Pattern::new(pattern)
+                .expect("Invalid pattern")
+                .matches(Path::new(path))
+        };
+
+        assert!(matches("*.norg", "todo.norg"));
+        assert!(!matches("*.norg", "projects/todo.norg"));
+        assert!(matches("projects/**/*.norg", "projects/todo.norg"));
+        assert!(matches("projects/**/*.norg", "projects/a/b/todo.norg"));
+        assert!(!matches("projects/**/*.norg", "other/todo.norg"));
+        assert!(matches("file?.norg", "file1.norg"));
+        assert!(matches("file[0-9].norg", "file1.norg"));
+        assert!(!matches("file[!0-9].norg", "file1.norg"));
+        assert!(matches("./**", "a/b"));
+    }
+
+    #[test]
+    fn test_pattern_errors() {
+        assert!(matches!(
+            Pattern::new("/etc/*"),
+            Err(GlobError::Absolute(_))
+        ));
+        assert!(matches!(
+            Pattern::new("../*.norg"),
+            Err(GlobError::EscapesRoot(_))
+        ));
+        assert!(matches!(
+            Pattern::new("[abc.norg"),
+            Err(GlobError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_glob() {
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
+
+        let mut files = workspace.glob("file*.norg").expect("Unable to glob");
+        files.sort();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("file1.norg"), PathBuf::from("file2.norg")]
+        );
+        assert!(workspace.glob("*.md").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::filesystem::FileSystem;
+use crate::glob::Pattern;
+use std::{io, path::Path};
+
+/// The name of the file at the workspace root listing files to leave out of the notes
+/// of the workspace, using gitignore-style patterns.
+pub const IGNORE_FILE_NAME: &str = ".norgignore";
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    /// Whether the rule re-includes files excluded by an earlier rule (`!pattern`).
+    negated: bool,
+    /// Whether the rule only applies to directories (`pattern/`).
+    dir_only: bool,
+}
+
+/// The rules of a workspace's [`IGNORE_FILE_NAME`] file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Reads the ignore file at the root of a workspace from its filesystem.
+    /// A missing ignore file means that nothing is ignored.
+    pub(crate) fn load(root: &Path, filesystem: &dyn FileSystem) -> io::Result<IgnoreRules> {
+        match filesystem.read(&root.join(IGNORE_FILE_NAME)) {
+            Ok(source) => String::from_utf8(source)
+                .map(|source| IgnoreRules::parse(&source))
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(IgnoreRules::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parses gitignore-style patterns, one per line: blank lines and lines starting with
+    /// `#` are skipped, a leading `!` re-includes matching files, a trailing `/` only
+    /// matches directories and patterns containing a `/` are relative to the workspace
+    /// root, while all others match at any depth. Invalid patterns are skipped.
+    pub(crate) fn parse(source: &str) -> IgnoreRules {
+        let rules = source
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(line) => (true, line),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(line) => (true, line),
+                    None => (false, line),
+                };
+
+                let pattern = if line.contains('/') {
+                    Pattern::new(line.trim_start_matches('/'))
+                } else {
+                    Pattern::new(&format!("**/{line}"))
+                };
+
+                Some(Rule {
+                    pattern: pattern.ok()?,
+                    negated,
+                    dir_only,
+                })
+            })
+            .collect();
+
+        IgnoreRules { rules }
+    }
+
+    /// Returns true if the path is ignored, with the last matching rule taking precedence.
+    ///
+    /// * `path`: The path relative to the workspace root.
+    /// * `is_dir`: Whether the path is a directory.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.pattern.matches(path))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = IgnoreRules::parse(
+            "# build output\n\nbuild/\n/drafts/*.norg\n*.tmp.norg\n!keep.tmp.norg\n",
+        );
+        let ignored = |path: &str, is_dir| rules.is_ignored(Path::new(path), is_dir);
+
+        assert!(ignored("build", true));
+        assert!(ignored("projects/build", true));
+        assert!(!ignored("build", false));
+        assert!(ignored("drafts/idea.norg", false));
+        assert!(!ignored("projects/drafts/idea.norg", false));
+        assert!(ignored("projects/a.tmp.norg", false));
+        assert!(!ignored("projects/keep.tmp.norg", false));
+        assert!(!ignored("todo.norg", false));
+    }
+}
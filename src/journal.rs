@@ -0,0 +1,225 @@
+use crate::workspace::Workspace;
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The folder, relative to the workspace root, that journal entries are stored in.
+pub const JOURNAL_FOLDER: &str = "journal";
+
+/// A calendar date identifying a journal entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Creates a new date, or returns None if the date does not exist.
+    ///
+    /// * `year`: The year, e.g. `2024`.
+    /// * `month`: The month of the year, from `1` to `12`.
+    /// * `day`: The day of the month, starting at `1`.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        let is_leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap_year => 29,
+            2 => 28,
+            _ => return None,
+        };
+
+        (1..=days_in_month)
+            .contains(&day)
+            .then_some(Date { year, month, day })
+    }
+
+    /// Returns the current date in the local time zone, like Neorg's journal.
+    /// Where the local time zone cannot be determined, the date in UTC is returned.
+    pub fn today() -> Date {
+        let seconds = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        };
+
+        #[cfg(unix)]
+        if let Some(date) = Date::local_from_seconds_since_epoch(seconds) {
+            return date;
+        }
+
+        Date::from_days_since_epoch(seconds.div_euclid(86_400))
+    }
+
+    /// Converts a number of seconds since the Unix epoch into the local date.
+    #[cfg(unix)]
+    fn local_from_seconds_since_epoch(seconds: i64) -> Option<Date> {
+        let time = libc::time_t::try_from(seconds).ok()?;
+        let mut tm = std::mem::MaybeUninit::<libc::tm>::uninit();
+
+        // SAFETY: `tm` is only read after `localtime_r` succeeded and initialized it.
+        let tm = unsafe {
+            if libc::localtime_r(&time, tm.as_mut_ptr()).is_null() {
+                return None;
+            }
+            tm.assume_init()
+        };
+
+        Date::new(
+            tm.tm_year + 1900,
+            u32::try_from(tm.tm_mon + 1).ok()?,
+            u32::try_from(tm.tm_mday).ok()?,
+        )
+    }
+
+    /// Converts a number of days since 1970-01-01 into a date.
+    pub(crate) fn from_days_since_epoch(days: i64) -> Date {
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+
+        Date { year, month, day }
+    }
+
+    /// Returns the year of the date.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Returns the month of the year, from `1` to `12`.
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// Returns the day of the month, starting at `1`.
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    /// Formats the date as an ISO 8601 date, e.g. `2024-06-01`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// How journal entries are laid out inside the journal folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JournalLayout {
+    /// A single file per day named after the ISO date, e.g. `journal/2024-06-01.norg`.
+    #[default]
+    Flat,
+    /// A folder per year and month, e.g. `journal/2024/06/01.norg`.
+    Nested,
+}
+
+impl Workspace {
+    /// Returns the path of the journal entry for the given date, using the flat layout.
+    /// The entry is not created.
+    ///
+    /// * `date`: The date of the journal entry.
+    pub fn journal_path(&self, date: Date) -> PathBuf {
+        self.journal_path_with_layout(date, JournalLayout::default())
+    }
+
+    /// Returns the path of the journal entry for the given date and layout, with the
+    /// workspace's [primary extension](Workspace::primary_extension).
+    /// The entry is not created.
+    ///
+    /// * `date`: The date of the journal entry.
+    /// * `layout`: How journal entries are laid out inside the journal folder.
+    pub fn journal_path_with_layout(&self, date: Date, layout: JournalLayout) -> PathBuf {
+        let journal = self.path.join(JOURNAL_FOLDER);
+
+        let extension = self.primary_extension();
+        match layout {
+            JournalLayout::Flat => journal.join(format!("{date}.{extension}")),
+            JournalLayout::Nested => journal
+                .join(format!("{:04}", date.year))
+                .join(format!("{:02}", date.month))
+                .join(format!("{:02}.{extension}", date.day)),
+        }
+    }
+
+    /// Returns the path of today's journal entry.
+    ///
+    /// * `layout`: How journal entries are laid out inside the journal folder.
+    pub fn today_journal_path(&self, layout: JournalLayout) -> PathBuf {
+        self.journal_path_with_layout(Date::today(), layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        assert_eq!(
+            Date::new(2024, 2, 29).map(|d| d.to_string()),
+            Some("2024-02-29".to_string())
+        );
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(2024, 13, 1), None);
+
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date::new(1970, 1, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(19_875),
+            Date::new(2024, 6, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(-1),
+            Date::new(1969, 12, 31).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_journal_path() {
+        let workspace = Workspace::with_path("example name", "/notes");
+        let date = Date::new(2024, 6, 1).unwrap();
+
+        assert_eq!(
+            workspace.journal_path(date),
+            PathBuf::from("/notes/journal/2024-06-01.norg")
+        );
+        assert_eq!(
+            workspace.journal_path_with_layout(date, JournalLayout::Nested),
+            PathBuf::from("/notes/journal/2024/06/01.norg")
+        );
+
+        let markdown = workspace.with_extensions(vec!["md".to_string()]);
+        assert_eq!(
+            markdown.journal_path(date),
+            PathBuf::from("/notes/journal/2024-06-01.md")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_local_date() {
+        // Whatever the local time zone, a date is at most a day away from the UTC date.
+        let local = Date::local_from_seconds_since_epoch(19_875 * 86_400 + 43_200)
+            .expect("Unable to determine the local date");
+        assert!(
+            (Date::new(2024, 5, 31).unwrap()..=Date::new(2024, 6, 2).unwrap()).contains(&local)
+        );
+    }
+}
@@ -4,6 +4,33 @@
 
 // Ways to query all the files in a given workspace
 
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod c_functions;
+pub mod config;
+pub mod disk;
+pub mod expand;
+pub mod filesystem;
+pub mod glob;
+pub mod ignore;
+pub mod journal;
+pub mod link;
+pub mod path;
+pub mod search;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "shared")]
+pub mod shared;
+pub mod template;
+pub mod title;
+pub mod uri;
+#[cfg(feature = "watch")]
+pub mod watch;
 pub mod workspace;
+#[cfg(feature = "async")]
+pub mod workspace_async;
+pub mod workspace_builder;
+pub mod workspace_diff;
+pub mod workspace_files;
 pub mod workspace_traverse;
+pub mod workspace_validate;
@@ -0,0 +1,296 @@
+use crate::path::normalize_lexically;
+use crate::workspace::{Workspace, WorkspaceManager};
+use std::{
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+/// An error that occurs while resolving a Neorg link into a path.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LinkError {
+    /// The link is not of the form `{:$workspace/path:}`, `{:$/path:}`, `{:path:}`
+    /// or a bare path.
+    Malformed(String),
+    /// The workspace named by the link does not exist.
+    UnknownWorkspace(String),
+    /// The link is relative to the current workspace, but there is no current workspace.
+    NoCurrentWorkspace,
+    /// The link points outside of its workspace.
+    EscapesWorkspace(String),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::Malformed(link) => write!(f, "malformed link \"{link}\""),
+            LinkError::UnknownWorkspace(name) => write!(f, "unknown workspace \"{name}\""),
+            LinkError::NoCurrentWorkspace => write!(f, "there is no current workspace"),
+            LinkError::EscapesWorkspace(link) => {
+                write!(f, "link \"{link}\" points outside of its workspace")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+/// Extracts the file part of a link, stripping the optional `{:` and `:}` delimiters
+/// as well as any target inside the file, e.g. `{:path:* Heading}` yields `path`.
+fn link_file(link: &str) -> Result<&str, LinkError> {
+    let malformed = || LinkError::Malformed(link.to_string());
+    let trimmed = link.trim();
+
+    let file = match trimmed.strip_prefix("{:") {
+        Some(inner) => {
+            let inner = inner.strip_suffix('}').ok_or_else(malformed)?;
+            let (file, _target) = inner.split_once(':').ok_or_else(malformed)?;
+            file
+        }
+        None if trimmed.contains(['{', '}']) => return Err(malformed()),
+        None => trimmed,
+    };
+
+    if file.is_empty() {
+        Err(malformed())
+    } else {
+        Ok(file)
+    }
+}
+
+/// Makes a path absolute and lexically normalized, treating relative paths
+/// as relative to the workspace root. Returns None if it lies outside the workspace.
+fn normalize_inside(root: &Path, path: &Path) -> Option<PathBuf> {
+    let path = normalize_lexically(&root.join(path));
+
+    path.starts_with(root).then_some(path)
+}
+
+impl Workspace {
+    /// Computes the relative path from one note to another, as used in a `{:path:}` link.
+    /// The path is relative to the directory containing `from` and, like Neorg links,
+    /// omits the extension of `to` if it is one of [`Workspace::extensions`].
+    /// Returns None if either path lies outside the workspace.
+    ///
+    /// * `from`: The note the link is inserted into.
+    /// * `to`: The note the link points to.
+    ///
+    /// Both paths may be absolute or relative to the workspace root.
+    pub fn relative_between(&self, from: &Path, to: &Path) -> Option<PathBuf> {
+        let root = normalize_lexically(&std::path::absolute(&self.path).ok()?);
+        let from = normalize_inside(&root, from)?;
+        let mut to = normalize_inside(&root, to)?;
+
+        if self.is_note(&to) {
+            to.set_extension("");
+        }
+
+        let from_dir: Vec<Component> = from.parent()?.components().collect();
+        let to: Vec<Component> = to.components().collect();
+        let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+        Some(
+            std::iter::repeat_n(Component::ParentDir, from_dir.len() - common)
+                .chain(to[common..].iter().copied())
+                .collect(),
+        )
+    }
+}
+
+impl WorkspaceManager {
+    /// Resolves a Neorg file link into the path of the `.norg` file it points to.
+    ///
+    /// Links of the form `{:$notes/projects/todo:}` point into the workspace named `notes`,
+    /// those of the form `{:$/projects/todo:}` into the current workspace, like links
+    /// without a leading `$`. The `{:` and `:}` delimiters are optional.
+    ///
+    /// * `link`: The link to resolve.
+    pub fn resolve_link(&self, link: &str) -> Result<PathBuf, LinkError> {
+        let (workspace, rel) = self.link_anchor(link)?;
+        let rel = rel.ok_or_else(|| LinkError::Malformed(link.to_string()))?;
+
+        workspace
+            .file_path(rel)
+            .ok_or_else(|| LinkError::EscapesWorkspace(link.to_string()))
+    }
+
+    /// Like [`WorkspaceManager::resolve_link`], but also accepting a bare `$` or `$name`
+    /// anchor, which resolves to the root directory of the current or named workspace.
+    ///
+    /// * `link`: The link to resolve, e.g. `$notes/projects/todo`, `$/projects/todo`,
+    ///   `projects/todo` or `$`.
+    pub fn resolve_anchor(&self, link: &str) -> Result<PathBuf, LinkError> {
+        match self.link_anchor(link)? {
+            (workspace, None) => Ok(workspace.path.clone()),
+            (workspace, Some(rel)) => workspace
+                .file_path(rel)
+                .ok_or_else(|| LinkError::EscapesWorkspace(link.to_string())),
+        }
+    }
+
+    /// Splits a link into the workspace it is anchored to and the note name inside it,
+    /// which is None if the link only names the workspace, like `$notes` or `$/`.
+    fn link_anchor<'a>(&self, link: &'a str) -> Result<(&Workspace, Option<&'a str>), LinkError> {
+        let file = link_file(link)?;
+
+        let Some(anchor) = file.strip_prefix('$') else {
+            let workspace = self
+                .get_current_workspace()
+                .ok_or(LinkError::NoCurrentWorkspace)?;
+
+            return Ok((workspace, Some(file)));
+        };
+
+        let (name, rel) = match anchor.split_once('/') {
+            Some((name, rel)) => (name, Some(rel).filter(|rel| !rel.is_empty())),
+            None => (anchor, None),
+        };
+        let workspace = if name.is_empty() {
+            self.get_current_workspace()
+                .ok_or(LinkError::NoCurrentWorkspace)?
+        } else {
+            self.get_workspace(name)
+                .ok_or_else(|| LinkError::UnknownWorkspace(name.to_string()))?
+        };
+
+        Ok((workspace, rel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_between() {
+        let workspace = Workspace::with_path("example name", "/notes");
+
+        let relative =
+            |from: &str, to: &str| workspace.relative_between(from.as_ref(), to.as_ref());
+
+        assert_eq!(
+            relative("projects/todo.norg", "projects/done.norg"),
+            Some(PathBuf::from("done"))
+        );
+        assert_eq!(
+            relative(
+                "/notes/projects/a/todo.norg",
+                "/notes/journal/2024-06-01.norg"
+            ),
+            Some(PathBuf::from("../../journal/2024-06-01"))
+        );
+        assert_eq!(
+            relative("index.norg", "projects/image.png"),
+            Some(PathBuf::from("projects/image.png"))
+        );
+        assert_eq!(relative("index.norg", "/elsewhere/note.norg"), None);
+        assert_eq!(relative("../outside.norg", "index.norg"), None);
+
+        let markdown =
+            Workspace::with_path("example name", "/notes").with_extensions(vec!["md".to_string()]);
+        assert_eq!(
+            markdown.relative_between("index.md".as_ref(), "projects/todo.md".as_ref()),
+            Some(PathBuf::from("projects/todo"))
+        );
+        assert_eq!(
+            markdown.relative_between("index.md".as_ref(), "projects/todo.norg".as_ref()),
+            Some(PathBuf::from("projects/todo.norg"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.resolve_link("{:$work/projects/todo:}"),
+            Ok(PathBuf::from("/work/projects/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_link("{:$work/todo:* Heading}"),
+            Ok(PathBuf::from("/work/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_link("projects/todo"),
+            Ok(PathBuf::from("/notes/projects/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_link("{:$/projects/todo:}"),
+            Ok(PathBuf::from("/notes/projects/todo.norg"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_anchor() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.resolve_anchor("$work/todo"),
+            Ok(PathBuf::from("/work/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_anchor("$/todo"),
+            Ok(PathBuf::from("/notes/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_anchor("todo"),
+            Ok(PathBuf::from("/notes/todo.norg"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_anchor("$"),
+            Ok(PathBuf::from("/notes"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_anchor("$work"),
+            Ok(PathBuf::from("/work"))
+        );
+        assert_eq!(
+            workspace_manager.resolve_anchor("$missing/todo"),
+            Err(LinkError::UnknownWorkspace("missing".to_string()))
+        );
+
+        workspace_manager.clear_current_workspace();
+        assert_eq!(
+            workspace_manager.resolve_anchor("$/todo"),
+            Err(LinkError::NoCurrentWorkspace)
+        );
+    }
+
+    #[test]
+    fn test_resolve_link_errors() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.resolve_link("{:$missing/todo:}"),
+            Err(LinkError::UnknownWorkspace("missing".to_string()))
+        );
+        assert_eq!(
+            workspace_manager.resolve_link("{:$notes/../todo:}"),
+            Err(LinkError::EscapesWorkspace(
+                "{:$notes/../todo:}".to_string()
+            ))
+        );
+        for link in ["{:todo", "{:todo}", "{::}", "$notes", "$", "to}do"] {
+            assert_eq!(
+                workspace_manager.resolve_link(link),
+                Err(LinkError::Malformed(link.to_string()))
+            );
+        }
+
+        workspace_manager.clear_current_workspace();
+        assert_eq!(
+            workspace_manager.resolve_link("todo"),
+            Err(LinkError::NoCurrentWorkspace)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fmt, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// An error returned by [`safe_join`] when a relative path would leave its base directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathEscape {
+    /// The offending relative path.
+    pub rel: String,
+}
+
+impl fmt::Display for PathEscape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" escapes its base directory", self.rel)
+    }
+}
+
+impl std::error::Error for PathEscape {}
+
+/// Joins a relative path onto a base directory, guaranteeing that the result stays
+/// inside `base`. The join is purely lexical: `.` components are dropped and `..` removes
+/// the previous component of `rel`, while absolute paths and any `..` stepping out of
+/// `base` are rejected. Symlinks are not resolved and `base` itself is kept as-is.
+///
+/// * `base`: The directory the result must stay inside of.
+/// * `rel`: The relative path to join, e.g. `projects/todo.norg`.
+pub fn safe_join(base: &Path, rel: &str) -> Result<PathBuf, PathEscape> {
+    let mut joined = base.to_path_buf();
+    let mut depth = 0_usize;
+
+    for component in Path::new(rel).components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(component) => {
+                joined.push(component);
+                depth += 1;
+            }
+            Component::ParentDir if depth > 0 => {
+                joined.pop();
+                depth -= 1;
+            }
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(PathEscape {
+                    rel: rel.to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(joined)
+}
+
+/// Canonicalizes a path that may not exist yet.
+/// The deepest existing ancestor is canonicalized (resolving symlinks) and the
+/// remaining components are appended lexically, with `..` removing the previous component.
+pub(crate) fn canonicalize_lenient(path: &Path) -> io::Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+    let mut ancestor = path.as_path();
+    let mut tail = Vec::new();
+
+    let mut canonical = loop {
+        match ancestor.canonicalize() {
+            Ok(canonical) => break canonical,
+            Err(err) => match (ancestor.parent(), ancestor.file_name()) {
+                (Some(parent), Some(name)) => {
+                    tail.push(Component::Normal(name));
+                    ancestor = parent;
+                }
+                (Some(parent), None) => {
+                    tail.push(Component::ParentDir);
+                    ancestor = parent;
+                }
+                (None, _) => return Err(err),
+            },
+        }
+    };
+
+    for component in tail.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                canonical.pop();
+            }
+            component => canonical.push(component),
+        }
+    }
+
+    Ok(canonical)
+}
+
+/// Lexically normalizes a path without touching the filesystem:
+/// `.` components and duplicate separators are removed and `..` removes the previous
+/// component. Leading `..` components of relative paths are preserved, while `..`
+/// directly after the root is dropped. Symlinks are not resolved.
+pub(crate) fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    if normalized.as_os_str().is_empty() {
+        normalized.push(Component::CurDir);
+    }
+
+    normalized
+}
+
+/// Whether the default filesystem of the target platform ignores case in paths,
+/// as on Windows and macOS. Linux filesystems are assumed to be case-sensitive.
+pub(crate) const CASE_INSENSITIVE_FS: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Returns true if `path` starts with `base` like [`Path::starts_with`], comparing
+/// the components case-insensitively if `case_insensitive` is set.
+pub(crate) fn starts_with_case(path: &Path, base: &Path, case_insensitive: bool) -> bool {
+    if !case_insensitive {
+        return path.starts_with(base);
+    }
+
+    let mut components = path.components();
+
+    base.components().all(|base| {
+        components.next().is_some_and(|component| {
+            component.as_os_str().to_string_lossy().to_lowercase()
+                == base.as_os_str().to_string_lossy().to_lowercase()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_join() {
+        let join = |rel: &str| safe_join(Path::new("/notes"), rel);
+        let escape = |rel: &str| {
+            Err(PathEscape {
+                rel: rel.to_string(),
+            })
+        };
+
+        assert_eq!(
+            join("projects/./todo.norg"),
+            Ok(PathBuf::from("/notes/projects/todo.norg"))
+        );
+        assert_eq!(join("a/../b.norg"), Ok(PathBuf::from("/notes/b.norg")));
+        assert_eq!(join(""), Ok(PathBuf::from("/notes")));
+        assert_eq!(join("../escape"), escape("../escape"));
+        assert_eq!(join("a/../../escape"), escape("a/../../escape"));
+        assert_eq!(join("/etc/passwd"), escape("/etc/passwd"));
+    }
+
+    #[test]
+    fn test_canonicalize_lenient() {
+        let root = Path::new("test/example_workspace")
+            .canonicalize()
+            .expect("The example workspace must exist");
+
+        assert_eq!(
+            canonicalize_lenient(Path::new("test/example_workspace/file1.norg")).unwrap(),
+            root.join("file1.norg")
+        );
+        assert_eq!(
+            canonicalize_lenient(Path::new("test/example_workspace/missing/../new.norg")).unwrap(),
+            root.join("new.norg")
+        );
+    }
+
+    #[test]
+    fn test_starts_with_case() {
+        let starts_with = |path: &str, base: &str, case_insensitive| {
+            starts_with_case(Path::new(path), Path::new(base), case_insensitive)
+        };
+
+        assert!(starts_with("/Notes/Todo.norg", "/notes", true));
+        assert!(!starts_with("/Notes/Todo.norg", "/notes", false));
+        assert!(starts_with("/notes/todo.norg", "/notes", false));
+        assert!(!starts_with("/notes", "/notes/todo.norg", true));
+        assert!(!starts_with("/notesx/todo.norg", "/NOTES", true));
+    }
+
+    #[test]
+    fn test_normalize_lexically() {
+        let normalize = |path: &str| normalize_lexically(Path::new(path));
+
+        assert_eq!(normalize("/a/./b//c/../d"), PathBuf::from("/a/b/d"));
+        assert_eq!(normalize("/../a"), PathBuf::from("/a"));
+        assert_eq!(normalize("../../a/b/.."), PathBuf::from("../../a"));
+        assert_eq!(normalize("a/../.."), PathBuf::from(".."));
+        assert_eq!(normalize("a/.."), PathBuf::from("."));
+    }
+}
@@ -0,0 +1,512 @@
+use crate::workspace::Workspace;
+use std::{io, iter::Peekable, path::PathBuf, str::Chars};
+
+/// Options controlling how [`Workspace::search`] matches the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Whether upper and lower case letters are distinguished. Defaults to true.
+    pub case_sensitive: bool,
+    /// Whether the query is a regular expression rather than a literal string.
+    /// Defaults to false.
+    ///
+    /// The supported syntax is a small subset of the usual regular expressions:
+    /// `.`, `^`, `$`, `*`, `+`, `?`, `|`, groups `(...)`, classes `[...]` and `[^...]`,
+    /// the escapes `\d`, `\w` and `\s` (and their negations `\D`, `\W` and `\S`),
+    /// and `\` to match any other character literally.
+    pub regex: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            case_sensitive: true,
+            regex: false,
+        }
+    }
+}
+
+/// A line of a note matching a search query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// The path of the note, relative to the workspace root.
+    pub path: PathBuf,
+    /// The line number of the match, starting at 1.
+    pub line: usize,
+    /// The contents of the matching line, without the line ending.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ClassItem::Range(start, end) => (start..=end).contains(&&c),
+            ClassItem::Digit(negated) => c.is_ascii_digit() != *negated,
+            ClassItem::Word(negated) => (c.is_alphanumeric() || c == '_') != *negated,
+            ClassItem::Space(negated) => c.is_whitespace() != *negated,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// `.`, matching any character.
+    Any,
+    /// `^`, matching the start of the line.
+    Start,
+    /// `$`, matching the end of the line.
+    End,
+    /// A literal character.
+    Char(char),
+    /// A character class, matching a single character in (or not in) the set of items.
+    Class {
+        negated: bool,
+        items: Vec<ClassItem>,
+    },
+    /// A parenthesized group of alternatives.
+    Group(Vec<Vec<Node>>),
+    /// A node repeated between `min` and `max` (unbounded if None) times.
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+/// An instruction of a compiled regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inst {
+    /// Matches a single character: [`Node::Any`], [`Node::Char`] or [`Node::Class`].
+    Char(Node),
+    /// Matches the start of the line.
+    Start,
+    /// Matches the end of the line.
+    End,
+    /// Continues at both instructions.
+    Split(usize, usize),
+    /// Continues at the instruction.
+    Jump(usize),
+    /// The whole pattern matched.
+    Match,
+}
+
+/// A compiled regular expression, matched by simulating all alternatives in lockstep
+/// rather than by backtracking. This takes time linear in the length of the line
+/// and constant stack space, whatever the pattern, since patterns come from users.
+struct Regex {
+    program: Vec<Inst>,
+    case_sensitive: bool,
+}
+
+impl Regex {
+    fn new(pattern: &str, case_sensitive: bool) -> Option<Regex> {
+        let mut chars = pattern.chars().peekable();
+        let alternatives = parse_alternatives(&mut chars)?;
+
+        // An unmatched `)` stops the parser before the end of the pattern.
+        if chars.next().is_some() {
+            return None;
+        }
+
+        let mut program = Vec::new();
+        compile_alternatives(&alternatives, &mut program);
+        program.push(Inst::Match);
+
+        Some(Regex {
+            program,
+            case_sensitive,
+        })
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        let chars: Vec<char> = line.chars().collect();
+        // The position + 1 at which each instruction was last added, so that every
+        // instruction is added at most once per position.
+        let mut seen = vec![0; self.program.len()];
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+
+        for i in 0..=chars.len() {
+            // A match may start at any position.
+            self.add_thread(&mut current, &mut seen, 0, i, chars.len());
+
+            if current.iter().any(|pc| self.program[*pc] == Inst::Match) {
+                return true;
+            }
+            if i == chars.len() {
+                break;
+            }
+
+            for pc in current.drain(..) {
+                if let Inst::Char(node) = &self.program[pc] {
+                    if self.matches_char(node, chars[i]) {
+                        self.add_thread(&mut next, &mut seen, pc + 1, i + 1, chars.len());
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        false
+    }
+
+    /// Adds the instructions reachable from `pc` without consuming a character at
+    /// position `i` to `threads`, following jumps and assertions.
+    fn add_thread(
+        &self,
+        threads: &mut Vec<usize>,
+        seen: &mut [usize],
+        pc: usize,
+        i: usize,
+        len: usize,
+    ) {
+        let mut stack = vec![pc];
+
+        while let Some(pc) = stack.pop() {
+            if seen[pc] == i + 1 {
+                continue;
+            }
+            seen[pc] = i + 1;
+
+            match self.program[pc] {
+                Inst::Jump(target) => stack.push(target),
+                Inst::Split(first, second) => stack.extend([second, first]),
+                Inst::Start if i == 0 => stack.push(pc + 1),
+                Inst::End if i == len => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Char(_) | Inst::Match => threads.push(pc),
+            }
+        }
+    }
+
+    fn matches_char(&self, node: &Node, c: char) -> bool {
+        let matches = |c: char| match node {
+            Node::Any => true,
+            Node::Char(expected) => c == *expected,
+            Node::Class { negated, items } => items.iter().any(|item| item.matches(c)) != *negated,
+            _ => false,
+        };
+
+        matches(c)
+            || !self.case_sensitive
+                && (c.to_lowercase().any(matches) || c.to_uppercase().any(matches))
+    }
+}
+
+/// Appends the instructions matching any of the alternatives to the program.
+fn compile_alternatives(alternatives: &[Vec<Node>], program: &mut Vec<Inst>) {
+    let mut jumps = Vec::new();
+
+    for (index, nodes) in alternatives.iter().enumerate() {
+        if index + 1 == alternatives.len() {
+            compile_sequence(nodes, program);
+        } else {
+            let split = program.len();
+            program.push(Inst::Split(split + 1, 0));
+            compile_sequence(nodes, program);
+            jumps.push(program.len());
+            program.push(Inst::Jump(0));
+            program[split] = Inst::Split(split + 1, program.len());
+        }
+    }
+
+    let end = program.len();
+    for jump in jumps {
+        program[jump] = Inst::Jump(end);
+    }
+}
+
+fn compile_sequence(nodes: &[Node], program: &mut Vec<Inst>) {
+    for node in nodes {
+        compile_node(node, program);
+    }
+}
+
+fn compile_node(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Group(alternatives) => compile_alternatives(alternatives, program),
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile_node(node, program);
+            }
+
+            match max {
+                // An iteration matching nothing ends the loop, as every instruction
+                // is only added once per position.
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile_node(node, program);
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile_node(node, program);
+                    }
+
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+        node => program.push(Inst::Char(node.clone())),
+    }
+}
+
+fn parse_alternatives(chars: &mut Peekable<Chars>) -> Option<Vec<Vec<Node>>> {
+    let mut alternatives = vec![parse_sequence(chars)?];
+
+    while chars.next_if_eq(&'|').is_some() {
+        alternatives.push(parse_sequence(chars)?);
+    }
+
+    Some(alternatives)
+}
+
+fn parse_sequence(chars: &mut Peekable<Chars>) -> Option<Vec<Node>> {
+    let mut nodes = Vec::new();
+
+    while let Some(c) = chars.next_if(|c| *c != '|' && *c != ')') {
+        let node = match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '*' | '+' | '?' => return None,
+            '(' => {
+                let alternatives = parse_alternatives(chars)?;
+                chars.next_if_eq(&')')?;
+                Node::Group(alternatives)
+            }
+            '[' => parse_class(chars)?,
+            '\\' => match parse_escape(chars.next()?) {
+                Ok(item) => Node::Class {
+                    negated: false,
+                    items: vec![item],
+                },
+                Err(c) => Node::Char(c),
+            },
+            c => Node::Char(c),
+        };
+
+        let (min, max) = match chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            _ => {
+                nodes.push(node);
+                continue;
+            }
+        };
+
+        chars.next();
+        nodes.push(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+        });
+    }
+
+    Some(nodes)
+}
+
+/// Parses the character after a `\`, returning the class it stands for
+/// or the character itself if it is matched literally.
+fn parse_escape(c: char) -> Result<ClassItem, char> {
+    match c {
+        'd' | 'D' => Ok(ClassItem::Digit(c == 'D')),
+        'w' | 'W' => Ok(ClassItem::Word(c == 'W')),
+        's' | 'S' => Ok(ClassItem::Space(c == 'S')),
+        c => Err(c),
+    }
+}
+
+fn parse_class(chars: &mut Peekable<Chars>) -> Option<Node> {
+    let negated = chars.next_if_eq(&'^').is_some();
+    let mut items = Vec::new();
+
+    // A `]` directly after the opening bracket is matched literally.
+    if let Some(c) = chars.next_if_eq(&']') {
+        items.push(ClassItem::Range(c, c));
+    }
+
+    loop {
+        let start = match chars.next()? {
+            ']' => break,
+            '\\' => match parse_escape(chars.next()?) {
+                Ok(item) => {
+                    items.push(item);
+                    continue;
+                }
+                Err(c) => c,
+            },
+            c => c,
+        };
+
+        match chars.next_if_eq(&'-') {
+            Some(_) if chars.peek().is_some_and(|c| *c != ']') => {
+                items.push(ClassItem::Range(start, chars.next()?));
+            }
+            Some(dash) => {
+                items.push(ClassItem::Range(start, start));
+                items.push(ClassItem::Range(dash, dash));
+            }
+            None => items.push(ClassItem::Range(start, start)),
+        }
+    }
+
+    Some(Node::Class { negated, items })
+}
+
+impl Workspace {
+    /// Searches the contents of all `.norg` files in the workspace, returning every
+    /// matching line. Files that are not valid UTF-8 or cannot be read are skipped.
+    ///
+    /// Returns an `InvalidInput` error if the query is not a valid regular expression.
+    ///
+    /// * `query`: The text or regular expression to search for.
+    /// * `opts`: How the query is matched, see [`SearchOptions`].
+    pub fn search(&self, query: &str, opts: SearchOptions) -> io::Result<Vec<SearchHit>> {
+        let matcher: Box<dyn Fn(&str) -> bool> = if opts.regex {
+            let regex = Regex::new(query, opts.case_sensitive).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid regular expression \"{query}\""),
+                )
+            })?;
+
+            Box::new(move |line| regex.is_match(line))
+        } else if opts.case_sensitive {
+            Box::new(move |line| line.contains(query))
+        } else {
+            let query = query.to_lowercase();
+
+            Box::new(move |line| line.to_lowercase().contains(&query))
+        };
+
+        let root = std::path::absolute(&self.path)?;
+        let mut hits = Vec::new();
+
+        for path in self.files()? {
+            let Ok(contents) = self.filesystem().read(&root.join(&path)) else {
+                continue;
+            };
+            let Ok(contents) = String::from_utf8(contents) else {
+                continue;
+            };
+
+            for (index, line) in contents.lines().enumerate() {
+                if matcher(line) {
+                    hits.push(SearchHit {
+                        path: path.clone(),
+                        line: index + 1,
+                        text: line.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_regex() {
+        let matches = |pattern: &str, line: &str| {
+            Regex::new(pattern, true)
+                .expect("Invalid regular expression")
+                .is_match(line)
+        };
+
+        assert!(matches("todo", "a todo item"));
+        assert!(matches("^\\* [A-Z]\\w+$", "* Heading"));
+        assert!(!matches("^\\* [A-Z]\\w+$", "** Heading"));
+        assert!(matches("colou?r", "color"));
+        assert!(matches("(cat|dog)s+", "dogsss"));
+        assert!(matches("\\d+-\\d+", "2024-06"));
+        assert!(!matches("[^a-z]", "abc"));
+        assert!(matches("(a*)+b", "b"));
+        assert!(!Regex::new("todo", false).unwrap().is_match("nothing"));
+        assert!(Regex::new("TODO", false).unwrap().is_match("a todo item"));
+
+        assert!(matches("a(b|)*c", "abbc"));
+        assert!(matches("x?y?$", "abc"));
+
+        for pattern in ["(todo", "todo)", "*todo", "[todo", "todo\\"] {
+            assert!(Regex::new(pattern, true).is_none(), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_regex_pathological() {
+        // Matching must neither recurse per character nor backtrack exponentially.
+        let long_line = "a".repeat(200_000);
+        let regex = Regex::new(".*x", true).unwrap();
+        assert!(!regex.is_match(&long_line));
+        assert!(regex.is_match(&format!("{long_line}x")));
+
+        let regex = Regex::new("(a|a)*c", true).unwrap();
+        assert!(!regex.is_match(&"a".repeat(28)));
+        assert!(regex.is_match(&format!("{}c", "a".repeat(28))));
+    }
+
+    #[test]
+    fn test_search() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        fs::write(dir.path().join("todo.norg"), "* Todo\n- ( ) Buy milk\n").unwrap();
+        fs::write(dir.path().join("notes.md"), "- ( ) Buy milk\n").unwrap();
+        fs::write(dir.path().join("binary.norg"), b"milk\n\xff\xfe\n").unwrap();
+
+        assert_eq!(
+            workspace
+                .search("milk", SearchOptions::default())
+                .expect("Unable to search the workspace"),
+            vec![SearchHit {
+                path: PathBuf::from("todo.norg"),
+                line: 2,
+                text: "- ( ) Buy milk".to_string(),
+            }]
+        );
+
+        let insensitive = SearchOptions {
+            case_sensitive: false,
+            ..SearchOptions::default()
+        };
+        assert_eq!(workspace.search("TODO", insensitive).unwrap().len(), 1);
+        assert!(workspace
+            .search("TODO", SearchOptions::default())
+            .unwrap()
+            .is_empty());
+
+        let regex = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(workspace.search("^\\* \\w+$", regex).unwrap()[0].line, 1);
+        assert_eq!(
+            workspace.search("(", regex).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::workspace::{Workspace, WorkspaceManager};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The serialized form of a [`WorkspaceManager`]: the list of workspaces
+/// alongside the names of the current and default workspaces.
+#[derive(Serialize)]
+struct SerializeWorkspaceManager<'a> {
+    workspaces: Vec<&'a Workspace>,
+    current_workspace: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_workspace: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct DeserializeWorkspaceManager {
+    workspaces: Vec<Workspace>,
+    current_workspace: Option<String>,
+    #[serde(default)]
+    default_workspace: Option<String>,
+}
+
+impl Serialize for WorkspaceManager {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut workspaces: Vec<&Workspace> = self.workspaces.values().collect();
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        SerializeWorkspaceManager {
+            workspaces,
+            current_workspace: self.current_workspace.as_deref(),
+            default_workspace: self.default_workspace.as_deref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorkspaceManager {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = DeserializeWorkspaceManager::deserialize(deserializer)?;
+
+        // Like the constructors, reject invalid and duplicate names rather than silently
+        // keeping only one of the workspaces.
+        let mut workspace_manager = WorkspaceManager::without_current_workspace(data.workspaces)
+            .map_err(D::Error::custom)?;
+
+        // Data written before the default was tracked separately only has a current workspace.
+        if let Some(default_workspace) = data.default_workspace.or(data.current_workspace.clone()) {
+            workspace_manager
+                .set_default_workspace(default_workspace)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(current_workspace) = data.current_workspace {
+            workspace_manager
+                .set_current_workspace(current_workspace)
+                .map_err(D::Error::custom)?;
+            workspace_manager.recent_workspaces.clear();
+        }
+
+        Ok(workspace_manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_manager_round_trip() {
+        let workspace1 = Workspace::with_path("example name", "~/some/path");
+
+        let workspace2 = Workspace::with_path("another example name", "~/another/path");
+
+        let mut workspace_manager = WorkspaceManager::new(
+            vec![workspace1.clone(), workspace2.clone()],
+            &workspace2.name,
+        )
+        .expect("Workspace 2 not found!");
+        workspace_manager
+            .set_default_workspace(&workspace1.name)
+            .unwrap();
+
+        let json = serde_json::to_string(&workspace_manager).expect("Unable to serialize");
+        assert!(json.contains(r#""path":"~/some/path""#));
+
+        let deserialized: WorkspaceManager =
+            serde_json::from_str(&json).expect("Unable to deserialize");
+        assert_eq!(
+            deserialized.current_workspace_name(),
+            Some(workspace2.name.as_str())
+        );
+        assert_eq!(deserialized.default_workspace, Some(workspace1.name));
+        assert_eq!(deserialized.workspaces.len(), 2);
+    }
+
+    #[test]
+    fn test_workspace_manager_deserialize_missing_current() {
+        let json = r#"{
+            "workspaces": [{ "name": "notes", "path": "~/notes" }],
+            "current_workspace": "work"
+        }"#;
+
+        let err = serde_json::from_str::<WorkspaceManager>(json)
+            .expect_err("A missing current workspace must be rejected");
+        assert!(err.to_string().contains("workspace \"work\" not found"));
+    }
+
+    #[test]
+    fn test_workspace_manager_deserialize_invalid_names() {
+        let json = r#"{
+            "workspaces": [
+                { "name": "notes", "path": "~/notes" },
+                { "name": " notes ", "path": "~/other" }
+            ],
+            "current_workspace": null
+        }"#;
+        assert!(serde_json::from_str::<WorkspaceManager>(json)
+            .expect_err("Duplicate names must be rejected")
+            .to_string()
+            .contains("already exists"));
+
+        let json = r#"{
+            "workspaces": [{ "name": " ", "path": "~/notes" }],
+            "current_workspace": null
+        }"#;
+        assert!(serde_json::from_str::<WorkspaceManager>(json)
+            .expect_err("Empty names must be rejected")
+            .to_string()
+            .contains("empty"));
+    }
+}
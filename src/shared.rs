@@ -0,0 +1,130 @@
+use crate::workspace::{Workspace, WorkspaceError, WorkspaceManager, WorkspaceNotFound};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A workspace manager that can be shared and mutated across threads.
+///
+/// Cloning it is cheap and yields a handle to the same manager. Every method locks
+/// the manager only for its own duration and returns owned data, so no lock is ever
+/// held across calls.
+#[derive(Debug, Clone)]
+pub struct SharedWorkspaceManager {
+    inner: Arc<RwLock<WorkspaceManager>>,
+}
+
+impl SharedWorkspaceManager {
+    /// Wraps a workspace manager so it can be shared across threads.
+    ///
+    /// * `workspace_manager`: The workspace manager to share.
+    pub fn new(workspace_manager: WorkspaceManager) -> SharedWorkspaceManager {
+        SharedWorkspaceManager {
+            inner: Arc::new(RwLock::new(workspace_manager)),
+        }
+    }
+
+    // Every method of the manager leaves it in a valid state, so a panic in another
+    // thread holding the lock cannot corrupt it and poisoning is safe to ignore.
+    fn read_lock(&self) -> RwLockReadGuard<'_, WorkspaceManager> {
+        self.inner.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn write_lock(&self) -> RwLockWriteGuard<'_, WorkspaceManager> {
+        self.inner.write().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Runs a function with shared access to the workspace manager.
+    ///
+    /// * `f`: The function to run. Other readers may run concurrently, writers are blocked.
+    pub fn read<R>(&self, f: impl FnOnce(&WorkspaceManager) -> R) -> R {
+        f(&self.read_lock())
+    }
+
+    /// Runs a function with exclusive access to the workspace manager,
+    /// e.g. to perform several changes atomically.
+    ///
+    /// * `f`: The function to run. All other readers and writers are blocked.
+    pub fn write<R>(&self, f: impl FnOnce(&mut WorkspaceManager) -> R) -> R {
+        f(&mut self.write_lock())
+    }
+
+    /// Returns a copy of the workspace with the given name, or None if it doesn't exist.
+    ///
+    /// * `name`: The name of the workspace.
+    pub fn get_workspace(&self, name: &str) -> Option<Workspace> {
+        self.read_lock().get_workspace(name).cloned()
+    }
+
+    /// Returns a copy of the current workspace, or None if there is no current workspace.
+    pub fn current_workspace(&self) -> Option<Workspace> {
+        self.read_lock().get_current_workspace().cloned()
+    }
+
+    /// Returns the name of the current workspace, or None if there is no current workspace.
+    pub fn current_workspace_name(&self) -> Option<String> {
+        self.read_lock()
+            .current_workspace_name()
+            .map(str::to_string)
+    }
+
+    /// Sets the current workspace to the workspace with the given name.
+    /// See [`WorkspaceManager::set_current_workspace`].
+    ///
+    /// * `name`: The name of the workspace to set as the current workspace.
+    pub fn set_current_workspace(&self, name: impl Into<String>) -> Result<(), WorkspaceNotFound> {
+        self.write_lock().set_current_workspace(name)
+    }
+
+    /// Adds a workspace, overwriting any existing workspace with the same name.
+    /// Returns an error if the name is not valid, see [`Workspace::validate_name`].
+    ///
+    /// * `workspace`: The workspace to add.
+    pub fn add_workspace(&self, workspace: Workspace) -> Result<(), WorkspaceError> {
+        self.write_lock().add_workspace(workspace)
+    }
+
+    /// Returns a copy of the shared workspace manager as it is right now.
+    pub fn snapshot(&self) -> WorkspaceManager {
+        self.read_lock().clone()
+    }
+}
+
+impl From<WorkspaceManager> for SharedWorkspaceManager {
+    fn from(workspace_manager: WorkspaceManager) -> Self {
+        SharedWorkspaceManager::new(workspace_manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_shared_workspace_manager() {
+        let shared = SharedWorkspaceManager::new(
+            WorkspaceManager::builder()
+                .workspace("notes", "/notes")
+                .workspace("work", "/work")
+                .build()
+                .expect("Unable to build the workspace manager"),
+        );
+
+        let handle = shared.clone();
+        thread::spawn(move || handle.set_current_workspace("work"))
+            .join()
+            .unwrap()
+            .expect("Unable to set the current workspace");
+
+        assert_eq!(shared.current_workspace_name().as_deref(), Some("work"));
+        assert_eq!(
+            shared.current_workspace().unwrap().path.to_str(),
+            Some("/work")
+        );
+        assert!(shared.set_current_workspace("missing").is_err());
+
+        shared
+            .add_workspace(Workspace::with_path("journal", "/journal"))
+            .unwrap();
+        assert_eq!(shared.read(|manager| manager.len()), 3);
+        assert!(shared.get_workspace("journal").is_some());
+    }
+}
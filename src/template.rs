@@ -0,0 +1,137 @@
+use crate::workspace::{Workspace, WorkspaceError};
+use crate::workspace_files::WriteMode;
+use std::{collections::HashMap, io, path::PathBuf};
+
+/// How placeholders without a value are handled when instantiating a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownPlaceholder {
+    /// Leave the placeholder in the note as-is.
+    #[default]
+    Keep,
+    /// Fail with an `InvalidInput` error.
+    Error,
+}
+
+/// Substitutes every `{{var}}` placeholder in a template with its value.
+/// Whitespace around the variable name is ignored, so `{{ title }}` works as well.
+///
+/// * `template`: The template source.
+/// * `vars`: The values of the placeholders.
+/// * `unknown`: How to handle placeholders without a value.
+pub fn render(
+    template: &str,
+    vars: &HashMap<String, String>,
+    unknown: UnknownPlaceholder,
+) -> io::Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + len + 2];
+        let name = placeholder[2..placeholder.len() - 2].trim();
+
+        rendered.push_str(&rest[..start]);
+
+        match (vars.get(name), unknown) {
+            (Some(value), _) => rendered.push_str(value),
+            (None, UnknownPlaceholder::Keep) => rendered.push_str(placeholder),
+            (None, UnknownPlaceholder::Error) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no value for template placeholder \"{name}\""),
+                ))
+            }
+        }
+
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+impl Workspace {
+    /// Creates a new note from a template, substituting `{{var}}` placeholders.
+    /// Placeholders without a value are left untouched.
+    /// See [`Workspace::create_from_template_with`].
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `template`: The template source.
+    /// * `vars`: The values of the placeholders.
+    pub fn create_from_template(
+        &self,
+        rel: &str,
+        template: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<PathBuf, WorkspaceError> {
+        self.create_from_template_with(rel, template, vars, UnknownPlaceholder::Keep)
+    }
+
+    /// Creates a new note from a template and returns its absolute path.
+    /// The note is written atomically and an existing note is never overwritten.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `template`: The template source.
+    /// * `vars`: The values of the placeholders.
+    /// * `unknown`: How to handle placeholders without a value.
+    pub fn create_from_template_with(
+        &self,
+        rel: &str,
+        template: &str,
+        vars: &HashMap<String, String>,
+        unknown: UnknownPlaceholder,
+    ) -> Result<PathBuf, WorkspaceError> {
+        let contents = render(template, vars, unknown)?;
+
+        self.write_file(rel, &contents, WriteMode::CreateNew)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars = HashMap::from([
+            ("title".to_string(), "Todo".to_string()),
+            ("date".to_string(), "2024-06-01".to_string()),
+        ]);
+
+        assert_eq!(
+            render("* {{title}} ({{ date }})", &vars, UnknownPlaceholder::Keep).unwrap(),
+            "* Todo (2024-06-01)"
+        );
+        assert_eq!(
+            render("{{author}} {{title", &vars, UnknownPlaceholder::Keep).unwrap(),
+            "{{author}} {{title"
+        );
+        assert_eq!(
+            render("{{author}}", &vars, UnknownPlaceholder::Error)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_create_from_template() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+        let vars = HashMap::from([("title".to_string(), "Todo".to_string())]);
+
+        let path = workspace
+            .create_from_template("todo", "* {{title}}\n", &vars)
+            .expect("Unable to create the note");
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "* Todo\n");
+
+        let err = workspace
+            .create_from_template("todo", "* {{title}}\n", &vars)
+            .expect_err("An existing note must not be overwritten");
+        assert!(matches!(err, WorkspaceError::AlreadyExists(_)));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::workspace::Workspace;
+use std::path::Path;
+
+/// How the words of a title derived from a file name are capitalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleCase {
+    /// Capitalize the first letter of every word, e.g. `Todo List`.
+    #[default]
+    Title,
+    /// Capitalize only the first letter of the first word, e.g. `Todo list`.
+    Sentence,
+    /// Keep the casing of the file name, e.g. `todo list`.
+    Preserve,
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl Workspace {
+    /// Derives a human-readable title from the file name of a note, e.g. `Todo List` for
+    /// `projects/todo_list.norg`. See [`Workspace::display_title_with`].
+    ///
+    /// * `rel`: The path of the note, relative to the workspace root.
+    pub fn display_title(&self, rel: &Path) -> String {
+        self.display_title_with(rel, TitleCase::default())
+    }
+
+    /// Derives a human-readable title from the file name of a note: the extension is
+    /// stripped, `_` and `-` become spaces and the words are capitalized according to
+    /// `case`. Only the first letter of a word is ever changed, so acronyms like `API`
+    /// keep their casing.
+    ///
+    /// * `rel`: The path of the note, relative to the workspace root.
+    /// * `case`: How to capitalize the words of the title.
+    pub fn display_title_with(&self, rel: &Path, case: TitleCase) -> String {
+        let stem = rel.file_stem().unwrap_or_default().to_string_lossy();
+        let words = stem.split(['_', '-', ' ']).filter(|word| !word.is_empty());
+
+        let words: Vec<String> = match case {
+            TitleCase::Title => words.map(capitalize).collect(),
+            TitleCase::Sentence => words
+                .enumerate()
+                .map(|(i, word)| match i {
+                    0 => capitalize(word),
+                    _ => word.to_string(),
+                })
+                .collect(),
+            TitleCase::Preserve => words.map(str::to_string).collect(),
+        };
+
+        words.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_title() {
+        let workspace = Workspace::with_path("notes", "/notes");
+        let title = |rel: &str, case| workspace.display_title_with(Path::new(rel), case);
+
+        assert_eq!(
+            workspace.display_title(Path::new("projects/todo_list.norg")),
+            "Todo List"
+        );
+        assert_eq!(
+            title("my-API__notes.norg", TitleCase::Title),
+            "My API Notes"
+        );
+        assert_eq!(title("todo_list.norg", TitleCase::Sentence), "Todo list");
+        assert_eq!(title("todo_List.norg", TitleCase::Preserve), "todo List");
+        assert_eq!(title("ünicode.norg", TitleCase::Title), "Ünicode");
+        assert_eq!(title("", TitleCase::Title), "");
+    }
+}
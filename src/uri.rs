@@ -0,0 +1,157 @@
+use crate::path::{canonicalize_lenient, safe_join, PathEscape};
+use crate::workspace::Workspace;
+use std::path::{Path, PathBuf};
+
+/// Turns an absolute path into a `file://` URI, percent-encoding everything but
+/// unreserved characters and separators. Windows paths like `C:\notes` become
+/// `file:///C:/notes` and UNC paths like `\\server\share` become `file://server/share`.
+fn file_uri(path: &Path) -> String {
+    // Unix paths are arbitrary bytes, which are encoded as-is rather than replacing
+    // invalid UTF-8 with U+FFFD.
+    #[cfg(unix)]
+    let path = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let path = path.to_string_lossy().into_owned().into_bytes();
+
+    encode_file_uri(path, cfg!(windows))
+}
+
+/// Builds a `file://` URI from the bytes of a path, treating `\` as a separator and
+/// leading `\\` as a UNC path if `windows` is set.
+fn encode_file_uri(mut path: Vec<u8>, windows: bool) -> String {
+    if windows {
+        for byte in &mut path {
+            if *byte == b'\\' {
+                *byte = b'/';
+            }
+        }
+    }
+
+    // Canonicalized Windows paths carry a verbatim `\\?\` prefix, which URIs leave out.
+    // UNC paths turn the server into the authority of the URI.
+    let (unc, path) = match path.strip_prefix(b"//?/") {
+        Some(verbatim) if windows => match verbatim.strip_prefix(b"UNC/") {
+            Some(unc) => (true, unc),
+            None => (false, verbatim),
+        },
+        _ => match path.strip_prefix(b"//") {
+            Some(unc) if windows => (true, unc),
+            _ => (false, &path[..]),
+        },
+    };
+
+    let mut uri = String::from("file://");
+    if !unc && !path.starts_with(b"/") {
+        uri.push('/');
+    }
+
+    for (i, byte) in path.iter().copied().enumerate() {
+        let is_drive_colon = byte == b':' && i == 1 && path[0].is_ascii_alphabetic();
+
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) || is_drive_colon {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    uri
+}
+
+impl Workspace {
+    /// Returns the expanded and, where possible, canonicalized workspace root.
+    fn uri_root(&self) -> PathBuf {
+        let path = self.expanded_path();
+
+        canonicalize_lenient(&path)
+            .or_else(|_| std::path::absolute(&path))
+            .unwrap_or(path)
+    }
+
+    /// Returns the workspace root as a percent-encoded `file://` URI, e.g. for the
+    /// `workspaceFolders` of a language server. The path is expanded and canonicalized first.
+    pub fn root_uri(&self) -> String {
+        file_uri(&self.uri_root())
+    }
+
+    /// Returns a note of the workspace as a percent-encoded `file://` URI.
+    /// Like [`Workspace::file_path`], the primary extension is appended if the name
+    /// doesn't have one. Fails if the name escapes the workspace or is empty.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn file_uri(&self, rel: &str) -> Result<String, PathEscape> {
+        let root = self.uri_root();
+        let mut path = safe_join(&root, rel)?;
+
+        if path == root {
+            return Err(PathEscape {
+                rel: rel.to_string(),
+            });
+        }
+        if path.extension().is_none() {
+            path.set_extension(self.primary_extension());
+        }
+
+        Ok(file_uri(&path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            file_uri(Path::new("/home/user/my notes/ü.norg")),
+            "file:///home/user/my%20notes/%C3%BC.norg"
+        );
+        assert_eq!(
+            file_uri(Path::new("C:/Notes/a#b")),
+            "file:///C:/Notes/a%23b"
+        );
+    }
+
+    #[test]
+    fn test_file_uri_windows() {
+        let uri = |path: &str| encode_file_uri(path.as_bytes().to_vec(), true);
+
+        assert_eq!(uri(r"C:\Notes\a b"), "file:///C:/Notes/a%20b");
+        assert_eq!(uri(r"\\?\C:\Notes"), "file:///C:/Notes");
+        assert_eq!(uri(r"\\server\share\notes"), "file://server/share/notes");
+        assert_eq!(
+            uri(r"\\?\UNC\server\share\notes"),
+            "file://server/share/notes"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_uri_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/notes/caf\xE9.norg"));
+        assert_eq!(file_uri(path), "file:///notes/caf%E9.norg");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_workspace_uris() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let root = dir.path().canonicalize().unwrap().join("my notes");
+        let workspace = Workspace::with_path("notes", dir.path().join("my notes"));
+        let root_uri = format!("file://{}", root.to_str().unwrap().replace(' ', "%20"));
+
+        assert_eq!(workspace.root_uri(), root_uri);
+        assert_eq!(
+            workspace.file_uri("projects/todo"),
+            Ok(format!("{root_uri}/projects/todo.norg"))
+        );
+        assert_eq!(
+            workspace.file_uri("../escape"),
+            Err(PathEscape {
+                rel: "../escape".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::workspace::Workspace;
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// The default interval at which a watched workspace is polled for changes.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change to a `.norg` file inside a watched workspace.
+/// Paths are relative to the workspace root.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WorkspaceEvent {
+    /// A note was created.
+    Created(PathBuf),
+    /// A note was removed.
+    Removed(PathBuf),
+    /// The contents of a note changed.
+    Modified(PathBuf),
+}
+
+/// An error that occurs while starting to watch a workspace.
+#[derive(Debug)]
+pub enum WatchError {
+    /// The workspace path exists but is not a directory.
+    NotADirectory(PathBuf),
+    /// The workspace could not be read.
+    Io(io::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::NotADirectory(path) => {
+                write!(f, "\"{}\" is not a directory", path.display())
+            }
+            WatchError::Io(err) => write!(f, "unable to watch workspace: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WatchError::NotADirectory(_) => None,
+            WatchError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for WatchError {
+    fn from(err: io::Error) -> Self {
+        WatchError::Io(err)
+    }
+}
+
+/// The receiving end of a workspace watcher.
+/// Dereferences to a [`mpsc::Receiver`]; dropping it stops the watcher thread.
+pub struct Receiver<T> {
+    receiver: mpsc::Receiver<T>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl<T> Deref for Receiver<T> {
+    type Target = mpsc::Receiver<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The last known state of every note in a workspace, keyed by relative path.
+type Snapshot = HashMap<PathBuf, (SystemTime, u64)>;
+
+fn snapshot(workspace: &Workspace) -> io::Result<Snapshot> {
+    let root = std::path::absolute(&workspace.path)?;
+
+    Ok(workspace
+        .files()?
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(root.join(&path)).ok()?;
+            Some((path, (metadata.modified().ok()?, metadata.len())))
+        })
+        .collect())
+}
+
+fn diff(old: &Snapshot, new: &Snapshot) -> Vec<WorkspaceEvent> {
+    let mut events: Vec<WorkspaceEvent> = new
+        .iter()
+        .filter_map(|(path, state)| match old.get(path) {
+            None => Some(WorkspaceEvent::Created(path.clone())),
+            Some(old_state) if old_state != state => Some(WorkspaceEvent::Modified(path.clone())),
+            Some(_) => None,
+        })
+        .chain(
+            old.keys()
+                .filter(|path| !new.contains_key(*path))
+                .map(|path| WorkspaceEvent::Removed(path.clone())),
+        )
+        .collect();
+
+    events.sort_by(|a, b| event_path(a).cmp(event_path(b)));
+    events
+}
+
+fn event_path(event: &WorkspaceEvent) -> &PathBuf {
+    match event {
+        WorkspaceEvent::Created(path)
+        | WorkspaceEvent::Removed(path)
+        | WorkspaceEvent::Modified(path) => path,
+    }
+}
+
+impl Workspace {
+    /// Watches the workspace for created, removed and modified `.norg` files,
+    /// polling every [`DEFAULT_POLL_INTERVAL`].
+    /// See [`Workspace::watch_with_interval`].
+    pub fn watch(&self) -> Result<Receiver<WorkspaceEvent>, WatchError> {
+        self.watch_with_interval(DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Watches the workspace for created, removed and modified `.norg` files.
+    ///
+    /// Changes are debounced: they are only reported once the workspace has been
+    /// unchanged for a full interval, so bursts of writes (e.g. an editor writing a
+    /// temporary file and renaming it into place) collapse into a single event per note.
+    ///
+    /// * `interval`: How often the workspace is polled for changes.
+    pub fn watch_with_interval(
+        &self,
+        interval: Duration,
+    ) -> Result<Receiver<WorkspaceEvent>, WatchError> {
+        self.watch_batched(interval).map(|batches| {
+            let (sender, receiver) = mpsc::channel();
+            let stopped = Arc::clone(&batches.stopped);
+
+            thread::spawn(move || {
+                for event in batches.iter().flatten() {
+                    if sender.send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            Receiver { receiver, stopped }
+        })
+    }
+
+    /// Like [`Workspace::watch_with_interval`], but reporting all changes that happened
+    /// within the debounce window as a single batch, sorted by path, e.g. for an indexer
+    /// that processes changes in bulk. Dropping the receiver stops the watcher thread.
+    ///
+    /// * `debounce`: How often the workspace is polled for changes. A batch is only sent
+    ///   once the workspace has been unchanged for this long.
+    pub fn watch_batched(
+        &self,
+        debounce: Duration,
+    ) -> Result<Receiver<Vec<WorkspaceEvent>>, WatchError> {
+        self.ensure_std_filesystem("watching")?;
+        if !fs::metadata(&self.path)?.is_dir() {
+            return Err(WatchError::NotADirectory(self.path.clone()));
+        }
+
+        let workspace = self.clone();
+        let mut reported = snapshot(&workspace)?;
+        let mut latest = reported.clone();
+
+        let (sender, receiver) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = Arc::clone(&stopped);
+
+        thread::spawn(move || {
+            while !thread_stopped.load(Ordering::Relaxed) {
+                thread::sleep(debounce);
+
+                // The workspace may be temporarily unavailable (e.g. an unmounted drive),
+                // in which case we try again on the next poll.
+                let Ok(current) = snapshot(&workspace) else {
+                    continue;
+                };
+
+                if current != latest {
+                    latest = current;
+                    continue;
+                }
+
+                let events = diff(&reported, &latest);
+
+                if !events.is_empty() && sender.send(events).is_err() {
+                    break;
+                }
+
+                reported = latest.clone();
+            }
+        });
+
+        Ok(Receiver { receiver, stopped })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(20);
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_watch() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        let events = workspace
+            .watch_with_interval(INTERVAL)
+            .expect("Unable to watch the workspace");
+
+        fs::write(dir.path().join("note.norg"), "").unwrap();
+        fs::write(dir.path().join("attachment.png"), "").unwrap();
+        assert_eq!(
+            events.recv_timeout(TIMEOUT),
+            Ok(WorkspaceEvent::Created("note.norg".into()))
+        );
+
+        fs::write(dir.path().join("note.norg"), "* Heading").unwrap();
+        assert_eq!(
+            events.recv_timeout(TIMEOUT),
+            Ok(WorkspaceEvent::Modified("note.norg".into()))
+        );
+
+        fs::remove_file(dir.path().join("note.norg")).unwrap();
+        assert_eq!(
+            events.recv_timeout(TIMEOUT),
+            Ok(WorkspaceEvent::Removed("note.norg".into()))
+        );
+    }
+
+    #[test]
+    fn test_watch_batched() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+        fs::write(dir.path().join("old.norg"), "").unwrap();
+
+        let batches = workspace
+            .watch_batched(INTERVAL)
+            .expect("Unable to watch the workspace");
+
+        fs::write(dir.path().join("b.norg"), "").unwrap();
+        fs::write(dir.path().join("a.norg"), "").unwrap();
+        fs::remove_file(dir.path().join("old.norg")).unwrap();
+        assert_eq!(
+            batches.recv_timeout(TIMEOUT),
+            Ok(vec![
+                WorkspaceEvent::Created("a.norg".into()),
+                WorkspaceEvent::Created("b.norg".into()),
+                WorkspaceEvent::Removed("old.norg".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_watch_not_a_directory() {
+        let workspace = Workspace::with_path(
+            "example workspace",
+            PathBuf::from("test/example_workspace/file1.norg"),
+        );
+
+        assert!(matches!(
+            workspace.watch(),
+            Err(WatchError::NotADirectory(_))
+        ));
+    }
+}
@@ -1,147 +1,2342 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::expand::{expand_path, expand_placeholders, ExpandError};
+use crate::filesystem::FileSystem;
+use crate::path::{
+    canonicalize_lenient, normalize_lexically, safe_join, starts_with_case, PathEscape,
+    CASE_INSENSITIVE_FS,
+};
+use crate::workspace_files::CreateHook;
+use crate::workspace_traverse::NORG_EXTENSION;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+/// A named directory of notes.
+/// Two workspaces are equal if both their names and paths are equal; their metadata
+/// is not taken into account. Workspaces are ordered by name, then by path, so sorted
+/// workspaces appear in the order users expect in lists.
+///
+/// Fields added over time have default values, so struct literals should end with
+/// `..Default::default()`, e.g. `Workspace { name, path, ..Default::default() }`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Workspace {
     pub name: String,
+    /// The root directory of the workspace.
+    ///
+    /// When the workspace is constructed directly rather than through [`Workspace::new`]
+    /// this path is stored as-is and may contain an unexpanded `~` or `$VAR`.
     pub path: PathBuf,
+    /// Arbitrary per-workspace settings, e.g. the default template of new notes.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub metadata: HashMap<String, String>,
+    /// The extensions of files recognized as notes, without the leading `.`, the first
+    /// being appended to note names without one. Empty means `.norg` only.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub extensions: Vec<String>,
+    /// Whether the notes of the workspace must never be created, changed or deleted,
+    /// e.g. for an archive. Mutating methods fail with [`WorkspaceError::ReadOnly`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub read_only: bool,
+    /// The filesystem the notes of the workspace are read from and written to,
+    /// or None for the real filesystem. See [`Workspace::with_filesystem`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub filesystem: Option<Arc<dyn FileSystem>>,
 }
 
-pub struct WorkspaceManager<'a> {
-    pub workspaces: HashMap<&'a String, &'a Workspace>,
-    current_workspace: &'a String,
+impl PartialEq for Workspace {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.path == other.path
+    }
 }
 
-#[derive(Debug)]
+impl Eq for Workspace {}
+
+impl PartialOrd for Workspace {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Workspace {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.path.cmp(&other.path))
+    }
+}
+
+impl Hash for Workspace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.path.hash(state);
+    }
+}
+
+/// A collection of named workspaces, one of which may be the current workspace.
+///
+/// Like in Neorg, the configured default workspace is tracked separately from the current
+/// workspace, so switching workspaces doesn't change the default.
+///
+/// Workspace names are case-sensitive unless the manager is constructed with
+/// [`WorkspaceManager::new_case_insensitive`].
+#[derive(Debug, Clone)]
+pub struct WorkspaceManager {
+    pub(crate) workspaces: HashMap<String, Workspace>,
+    pub(crate) current_workspace: Option<String>,
+    pub(crate) default_workspace: Option<String>,
+    pub(crate) case_insensitive: bool,
+    pub(crate) fallback_workspace: Option<String>,
+    /// The names of the workspaces most recently made current, most recent first.
+    pub(crate) recent_workspaces: VecDeque<String>,
+    pub(crate) recent_limit: usize,
+    /// Alternative names of workspaces, mapping each alias to a workspace name.
+    pub(crate) aliases: HashMap<String, String>,
+    pub(crate) create_hooks: Hooks<CreateHook>,
+    pub(crate) change_hooks: Hooks<WorkspaceChangeHook>,
+}
+
+/// Callbacks registered on a workspace manager, invoked in registration order.
+/// Clones of a workspace manager share the same callbacks.
+pub(crate) struct Hooks<F: ?Sized>(pub(crate) Vec<Arc<F>>);
+
+impl<F: ?Sized> Default for Hooks<F> {
+    fn default() -> Self {
+        Hooks(Vec::new())
+    }
+}
+
+impl<F: ?Sized> Clone for Hooks<F> {
+    fn clone(&self) -> Self {
+        Hooks(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Hooks<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hooks({})", self.0.len())
+    }
+}
+
+/// A change of the current workspace, passed to the callbacks registered with
+/// [`WorkspaceManager::on_workspace_change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentWorkspaceChanged<'a> {
+    /// The name of the previous current workspace, or None if there was none.
+    pub from: Option<&'a str>,
+    /// The name of the new current workspace.
+    pub to: &'a str,
+}
+
+/// A callback registered with [`WorkspaceManager::on_workspace_change`].
+pub type WorkspaceChangeHook = dyn Fn(&CurrentWorkspaceChanged) + Send + Sync;
+
+/// The number of workspaces remembered by [`WorkspaceManager::recent_workspaces`] by default.
+pub const DEFAULT_RECENT_LIMIT: usize = 10;
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct WorkspaceNotFound {
     pub workspace: String,
 }
 
-impl<'a> WorkspaceManager<'a> {
-    /// Creates a new workspace manager with a single workspace, setting it as the default.
+impl fmt::Display for WorkspaceNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "workspace \"{}\" not found", self.workspace)
+    }
+}
+
+impl std::error::Error for WorkspaceNotFound {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WorkspaceIsCurrent {
+    pub workspace: String,
+}
+
+impl fmt::Display for WorkspaceIsCurrent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "workspace \"{}\" is the current workspace",
+            self.workspace
+        )
+    }
+}
+
+impl std::error::Error for WorkspaceIsCurrent {}
+
+/// How to resolve workspaces with the same name when merging workspace managers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the workspace that is already registered.
+    PreferSelf,
+    /// Replace the registered workspace with the incoming one.
+    PreferOther,
+    /// Fail the merge without modifying anything.
+    Error,
+}
+
+/// A reason why a string is not a valid workspace name.
+#[derive(Debug, PartialEq, Eq)]
+pub enum NameError {
+    /// The name is empty or consists only of whitespace.
+    Empty,
+    /// The name contains a path separator.
+    Separator(String),
+    /// The name contains a control character, e.g. a newline.
+    ControlCharacter(String),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "workspace name is empty"),
+            NameError::Separator(name) => {
+                write!(f, "workspace name \"{name}\" contains a path separator")
+            }
+            NameError::ControlCharacter(name) => {
+                write!(f, "workspace name {name:?} contains a control character")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// An error that occurs while constructing a workspace through [`Workspace::new`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidWorkspace {
+    /// The name is not a valid workspace name.
+    Name(NameError),
+    /// The path could not be expanded.
+    Path(ExpandError),
+}
+
+impl fmt::Display for InvalidWorkspace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidWorkspace::Name(err) => err.fmt(f),
+            InvalidWorkspace::Path(err) => write!(f, "invalid workspace path: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidWorkspace {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InvalidWorkspace::Name(err) => Some(err),
+            InvalidWorkspace::Path(err) => Some(err),
+        }
+    }
+}
+
+impl From<NameError> for InvalidWorkspace {
+    fn from(err: NameError) -> Self {
+        InvalidWorkspace::Name(err)
+    }
+}
+
+impl From<ExpandError> for InvalidWorkspace {
+    fn from(err: ExpandError) -> Self {
+        InvalidWorkspace::Path(err)
+    }
+}
+
+/// An error that occurs while operating on the workspaces of a workspace manager.
+///
+/// Two `Io` errors are considered equal if they are of the same [`io::ErrorKind`].
+#[derive(Debug)]
+pub enum WorkspaceError {
+    /// No workspace with the given name exists.
+    NotFound(WorkspaceNotFound),
+    /// A workspace or note with the given name already exists.
+    AlreadyExists(String),
+    /// The name is not a valid workspace name.
+    InvalidName(NameError),
+    /// A note name escapes its workspace.
+    PathEscape(PathEscape),
+    /// A workspace path could not be expanded.
+    InvalidPath(ExpandError),
+    /// The workspace with the given name is read-only, see [`Workspace::read_only`].
+    ReadOnly(String),
+    /// The filesystem operation failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::NotFound(err) => err.fmt(f),
+            WorkspaceError::AlreadyExists(name) => write!(f, "\"{name}\" already exists"),
+            WorkspaceError::InvalidName(err) => err.fmt(f),
+            WorkspaceError::PathEscape(err) => err.fmt(f),
+            WorkspaceError::InvalidPath(err) => write!(f, "invalid workspace path: {err}"),
+            WorkspaceError::ReadOnly(name) => write!(f, "workspace \"{name}\" is read-only"),
+            WorkspaceError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkspaceError::NotFound(err) => Some(err),
+            WorkspaceError::AlreadyExists(_) | WorkspaceError::ReadOnly(_) => None,
+            WorkspaceError::InvalidName(err) => Some(err),
+            WorkspaceError::PathEscape(err) => Some(err),
+            WorkspaceError::InvalidPath(err) => Some(err),
+            WorkspaceError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl PartialEq for WorkspaceError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WorkspaceError::NotFound(a), WorkspaceError::NotFound(b)) => a == b,
+            (WorkspaceError::AlreadyExists(a), WorkspaceError::AlreadyExists(b)) => a == b,
+            (WorkspaceError::InvalidName(a), WorkspaceError::InvalidName(b)) => a == b,
+            (WorkspaceError::PathEscape(a), WorkspaceError::PathEscape(b)) => a == b,
+            (WorkspaceError::InvalidPath(a), WorkspaceError::InvalidPath(b)) => a == b,
+            (WorkspaceError::ReadOnly(a), WorkspaceError::ReadOnly(b)) => a == b,
+            (WorkspaceError::Io(a), WorkspaceError::Io(b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for WorkspaceError {}
+
+impl From<io::Error> for WorkspaceError {
+    fn from(err: io::Error) -> Self {
+        WorkspaceError::Io(err)
+    }
+}
+
+impl From<PathEscape> for WorkspaceError {
+    fn from(err: PathEscape) -> Self {
+        WorkspaceError::PathEscape(err)
+    }
+}
+
+impl From<ExpandError> for WorkspaceError {
+    fn from(err: ExpandError) -> Self {
+        WorkspaceError::InvalidPath(err)
+    }
+}
+
+impl From<WorkspaceError> for io::Error {
+    fn from(err: WorkspaceError) -> Self {
+        let kind = match err {
+            WorkspaceError::Io(err) => return err,
+            WorkspaceError::NotFound(_) => io::ErrorKind::NotFound,
+            WorkspaceError::AlreadyExists(_) => io::ErrorKind::AlreadyExists,
+            WorkspaceError::InvalidName(_)
+            | WorkspaceError::PathEscape(_)
+            | WorkspaceError::InvalidPath(_) => io::ErrorKind::InvalidInput,
+            WorkspaceError::ReadOnly(_) => io::ErrorKind::ReadOnlyFilesystem,
+        };
+
+        io::Error::new(kind, err)
+    }
+}
+
+impl From<WorkspaceNotFound> for WorkspaceError {
+    fn from(err: WorkspaceNotFound) -> Self {
+        WorkspaceError::NotFound(err)
+    }
+}
+
+impl From<NameError> for WorkspaceError {
+    fn from(err: NameError) -> Self {
+        WorkspaceError::InvalidName(err)
+    }
+}
+
+impl fmt::Display for Workspace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.path.display())
+    }
+}
+
+impl Workspace {
+    /// Creates a new workspace, expanding a leading `~` (or `~user`) and any
+    /// `$VAR`/`${VAR}` occurrences in the path.
+    /// The name is trimmed and validated through [`Workspace::validate_name`].
+    /// If the name is invalid or the path cannot be expanded, an error is returned.
     ///
-    /// * `workspace`: The single workspace to use
-    pub fn from_single_workspace(workspace: &'a Workspace) -> WorkspaceManager<'a> {
-        WorkspaceManager {
-            current_workspace: &workspace.name,
-            workspaces: HashMap::from([(&workspace.name, workspace)]),
+    /// * `name`: The name of the workspace.
+    /// * `raw_path`: The unexpanded path to the root of the workspace.
+    pub fn new(name: impl Into<String>, raw_path: &str) -> Result<Workspace, InvalidWorkspace> {
+        let name = name.into();
+
+        Ok(Workspace {
+            name: Workspace::validate_name(&name)?.to_string(),
+            path: expand_path(raw_path)?,
+            metadata: HashMap::new(),
+            extensions: Vec::new(),
+            read_only: false,
+            filesystem: None,
+        })
+    }
+
+    /// Creates a new workspace from a path template, substituting `{name}` placeholders
+    /// with the given values before expanding `~` and environment variables like
+    /// [`Workspace::new`]. Placeholders without a value are an error.
+    /// Like with [`Workspace::new`], the name is trimmed and validated.
+    ///
+    /// * `name`: The name of the workspace.
+    /// * `template`: The path template, e.g. `{home}/work/{project}/notes`.
+    /// * `vars`: The values of the placeholders.
+    pub fn from_template(
+        name: &str,
+        template: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<Workspace, InvalidWorkspace> {
+        let name = Workspace::validate_name(name)?;
+        let path = expand_path(&expand_placeholders(template, vars)?)?;
+
+        Ok(Workspace::with_path(name, path))
+    }
+
+    /// Creates a new workspace from an already expanded path, taking ownership of the
+    /// name and path without copying them. Like constructing the struct directly with
+    /// `..Default::default()`, neither the name nor the path are validated or expanded.
+    ///
+    /// * `name`: The name of the workspace.
+    /// * `path`: The path to the root of the workspace.
+    pub fn with_path(name: impl Into<String>, path: impl Into<PathBuf>) -> Workspace {
+        Workspace {
+            name: name.into(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks that a string is a valid workspace name, returning it without surrounding
+    /// whitespace. Names must not be empty, nor contain path separators or control characters.
+    ///
+    /// * `name`: The name to validate.
+    pub fn validate_name(name: &str) -> Result<&str, NameError> {
+        let name = name.trim();
+
+        if name.is_empty() {
+            Err(NameError::Empty)
+        } else if name.contains(['/', '\\']) {
+            Err(NameError::Separator(name.to_string()))
+        } else if name.contains(char::is_control) {
+            Err(NameError::ControlCharacter(name.to_string()))
+        } else {
+            Ok(name)
+        }
+    }
+
+    /// Returns the root directory of the workspace.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the canonical, absolute form of the workspace path with all symlinks resolved.
+    ///
+    /// This queries the filesystem and therefore fails if the workspace directory
+    /// doesn't exist, which makes it suitable for validating workspaces on startup.
+    pub fn canonical_path(&self) -> std::io::Result<PathBuf> {
+        std::fs::canonicalize(&self.path)
+    }
+
+    /// Returns the workspace with a relative path resolved against a base directory,
+    /// e.g. the directory of the configuration file defining it. Absolute paths as well as
+    /// unexpanded paths starting with `~` or `$` are left unchanged.
+    ///
+    /// * `base`: The directory the workspace path is relative to.
+    pub fn resolve_relative_to(&self, base: &Path) -> Workspace {
+        let unexpanded = self
+            .path
+            .to_str()
+            .is_some_and(|path| path.starts_with(['~', '$']));
+
+        if self.path.is_absolute() || unexpanded {
+            self.clone()
+        } else {
+            Workspace {
+                path: base.join(&self.path),
+                ..self.clone()
+            }
+        }
+    }
+
+    /// Returns the workspace path with `.` and `..` components and duplicate separators
+    /// collapsed. Unlike [`Workspace::canonical_path`] this is purely lexical: symlinks
+    /// are not followed and the directory does not need to exist.
+    pub fn normalized_path(&self) -> PathBuf {
+        normalize_lexically(&self.path)
+    }
+
+    /// Returns the part of a path relative to the workspace root, e.g. `projects/todo.norg`,
+    /// or None if the path is not inside the workspace.
+    ///
+    /// Both paths are canonicalized first, so symlinks and `..` are resolved; since the
+    /// result may therefore differ from the tail of `abs`, an owned path is returned.
+    ///
+    /// * `abs`: The path to strip the workspace root from. It does not need to exist.
+    pub fn strip_prefix(&self, abs: &Path) -> Option<PathBuf> {
+        // Avoid touching the filesystem when the path is plainly inside the workspace.
+        if let Ok(relative) = abs.strip_prefix(&self.path) {
+            if relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Some(relative.to_path_buf());
+            }
+        }
+
+        let root = canonicalize_lenient(&self.path).ok()?;
+        let abs = canonicalize_lenient(abs).ok()?;
+
+        abs.strip_prefix(root).ok().map(Path::to_path_buf)
+    }
+
+    /// Returns true if the path lies inside the workspace. Both paths are canonicalized
+    /// first, like in [`Workspace::strip_prefix`], and compared case-insensitively on
+    /// platforms whose filesystems ignore case (Windows and macOS).
+    ///
+    /// * `path`: The path to check. It does not need to exist.
+    pub fn contains(&self, path: &Path) -> bool {
+        match (canonicalize_lenient(&self.path), canonicalize_lenient(path)) {
+            (Ok(root), Ok(path)) => starts_with_case(&path, &root, CASE_INSENSITIVE_FS),
+            _ => false,
+        }
+    }
+
+    /// Returns the workspace path with a leading `~` and any `$VAR` expanded, for workspaces
+    /// constructed directly rather than through [`Workspace::new`].
+    /// The path is returned as-is if it cannot be expanded.
+    pub(crate) fn expanded_path(&self) -> PathBuf {
+        self.path
+            .to_str()
+            .and_then(|path| expand_path(path).ok())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// Returns true if both workspaces point at the same directory, even if their paths
+    /// are spelled differently, e.g. `~/notes` and `/home/user/notes`.
+    /// Returns false if either directory doesn't exist.
+    ///
+    /// * `other`: The workspace to compare the location with.
+    pub fn same_location_as(&self, other: &Workspace) -> bool {
+        match (
+            std::fs::canonicalize(self.expanded_path()),
+            std::fs::canonicalize(other.expanded_path()),
+        ) {
+            (Ok(path), Ok(other_path)) => path == other_path,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the workspace path exists on its [`Workspace::filesystem`].
+    pub fn exists(&self) -> bool {
+        self.filesystem().exists(&self.path)
+    }
+
+    /// Returns the workspace marked as read-only (or writable), see [`Workspace::read_only`].
+    ///
+    /// * `read_only`: Whether the workspace must not be written to.
+    pub fn with_read_only(mut self, read_only: bool) -> Workspace {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Returns the workspace recognizing files with the given extensions as notes instead of
+    /// only `.norg` files, e.g. `["norg", "md"]`. The first extension is the primary one,
+    /// appended to note names without an extension.
+    ///
+    /// * `extensions`: The extensions, without the leading `.`.
+    pub fn with_extensions(mut self, extensions: Vec<String>) -> Workspace {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Returns the extensions of files recognized as notes, without the leading `.`.
+    pub fn extensions(&self) -> Vec<&str> {
+        if self.extensions.is_empty() {
+            vec![NORG_EXTENSION]
+        } else {
+            self.extensions.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Returns the extension appended to note names without one, `norg` by default.
+    pub fn primary_extension(&self) -> &str {
+        self.extensions
+            .first()
+            .map_or(NORG_EXTENSION, String::as_str)
+    }
+
+    /// Returns true if the file has one of the workspace's note extensions.
+    pub(crate) fn is_note(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| self.extensions().iter().any(|note_ext| ext == *note_ext))
+    }
+
+    /// Returns the workspace with a metadata entry added, overwriting any previous value.
+    ///
+    /// * `key`: The name of the setting.
+    /// * `value`: The value of the setting.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Workspace {
+        self.set_meta(key, value);
+        self
+    }
+
+    /// Returns the metadata value stored under the given key, or None if there is none.
+    ///
+    /// * `key`: The name of the setting.
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Stores a metadata value, returning the previous value under the same key.
+    ///
+    /// * `key`: The name of the setting.
+    /// * `value`: The value of the setting.
+    pub fn set_meta(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.metadata.insert(key.into(), value.into())
+    }
+
+    /// Resolves a note name relative to the workspace root into a full path.
+    /// Appends the primary extension (`.norg` by default) if the name doesn't already have one.
+    /// Returns None if the name is empty, absolute or escapes the workspace through `..`,
+    /// see [`safe_join`].
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn file_path(&self, rel: &str) -> Option<PathBuf> {
+        self.checked_file_path(rel).ok()
+    }
+
+    /// Like [`Workspace::file_path`], but returning a [`PathEscape`] error for invalid names.
+    pub(crate) fn checked_file_path(&self, rel: &str) -> Result<PathBuf, PathEscape> {
+        let mut path = self.join_relative(rel).ok_or_else(|| PathEscape {
+            rel: rel.to_string(),
+        })?;
+
+        if path.extension().is_none() {
+            path.set_extension(self.primary_extension());
         }
+
+        Ok(path)
+    }
+
+    /// Joins a relative path onto the workspace root through [`safe_join`], with the same
+    /// validation as [`Workspace::file_path`] but without appending the primary extension.
+    pub(crate) fn join_relative(&self, rel: &str) -> Option<PathBuf> {
+        safe_join(&self.path, rel)
+            .ok()
+            .filter(|path| *path != self.path)
+    }
+}
+
+impl WorkspaceManager {
+    /// Creates a new workspace manager with a single workspace, setting it as the default
+    /// and current workspace. Returns an error if the name is not valid, see
+    /// [`Workspace::validate_name`]; surrounding whitespace is removed from the name.
+    ///
+    /// * `workspace`: The single workspace to use
+    pub fn from_single_workspace(workspace: Workspace) -> Result<WorkspaceManager, WorkspaceError> {
+        let workspace_manager = WorkspaceManager::without_current_workspace(vec![workspace])?;
+        let current_workspace = workspace_manager.workspaces.keys().next().cloned();
+
+        Ok(WorkspaceManager {
+            default_workspace: current_workspace.clone(),
+            current_workspace,
+            ..workspace_manager
+        })
     }
 
-    /// Creates a new workspace manager from a list of workspaces.
-    /// If the default workspace is not found, an error is returned.
+    /// Creates a new workspace manager from a list of workspaces,
+    /// starting out with the default workspace as the current workspace.
+    /// If the default workspace is not found, an error is returned, as for invalid
+    /// or duplicate names like with [`WorkspaceManager::without_current_workspace`].
     ///
     /// * `workspaces`: A list of workspaces to add to the workspace manager.
     /// * `default_workspace`: The name of the default workspace.
     pub fn new(
-        workspaces: Vec<&'a Workspace>,
-        default_workspace: &'a String,
-    ) -> Result<WorkspaceManager<'a>, WorkspaceNotFound> {
-        if !workspaces.iter().any(|w| &w.name == default_workspace) {
+        workspaces: Vec<Workspace>,
+        default_workspace: &str,
+    ) -> Result<WorkspaceManager, WorkspaceError> {
+        let workspace_manager = WorkspaceManager::without_current_workspace(workspaces)?;
+        let default_workspace = default_workspace.trim();
+
+        if !workspace_manager.workspaces.contains_key(default_workspace) {
             Err(WorkspaceNotFound {
                 workspace: default_workspace.to_string(),
-            })
+            })?
         } else {
             Ok(WorkspaceManager {
-                current_workspace: default_workspace,
-                workspaces: workspaces
-                    .into_iter()
-                    .map(|w| (&w.name, w))
-                    .collect::<HashMap<_, _>>(),
+                current_workspace: Some(default_workspace.to_string()),
+                default_workspace: Some(default_workspace.to_string()),
+                ..workspace_manager
             })
         }
     }
 
-    /// Returns a workspace with the given name, or None if it doesn't exist.
+    /// Creates a new workspace manager whose workspace names are matched case-insensitively,
+    /// so that e.g. [`WorkspaceManager::get_workspace`] and
+    /// [`WorkspaceManager::set_current_workspace`] treat `Notes` and `notes` alike.
+    /// Returns an `AlreadyExists` error if two workspace names differ only in case.
     ///
-    /// * `name`: The name of the workspace.
-    pub fn get_workspace(&self, name: &String) -> Option<&Workspace> {
-        self.workspaces.get(name).cloned()
+    /// * `workspaces`: A list of workspaces to add to the workspace manager.
+    /// * `default_workspace`: The name of the default workspace, in any case.
+    pub fn new_case_insensitive(
+        workspaces: Vec<Workspace>,
+        default_workspace: &str,
+    ) -> Result<WorkspaceManager, WorkspaceError> {
+        let mut seen = HashMap::new();
+
+        for workspace in &workspaces {
+            if let Some(existing) = seen.insert(workspace.name.to_lowercase(), &workspace.name) {
+                if existing != &workspace.name {
+                    return Err(WorkspaceError::AlreadyExists(workspace.name.clone()));
+                }
+            }
+        }
+
+        let mut workspace_manager = WorkspaceManager::without_current_workspace(workspaces)?;
+        workspace_manager.case_insensitive = true;
+        workspace_manager.set_current_workspace(default_workspace)?;
+        workspace_manager.default_workspace = workspace_manager.current_workspace.clone();
+        // Like the other constructors, selecting the default doesn't count as recent use.
+        workspace_manager.recent_workspaces.clear();
+
+        Ok(workspace_manager)
     }
 
-    /// Sets the current workspace to the workspace with the given name.
-    /// Returns unit if the workspace was set, else returns a WorkspaceNotFound error.
+    /// Creates a new workspace manager from a list of workspaces without a current
+    /// or default workspace.
     ///
-    /// * `name`: The name of the workspace to set as the current workspace.
-    pub fn set_current_workspace(&mut self, name: &'a String) -> Result<(), WorkspaceNotFound> {
-        if self.workspaces.get(name).is_none() {
-            Err(WorkspaceNotFound {
-                workspace: name.to_string(),
-            })
-        } else {
-            self.current_workspace = name;
-            Ok(())
+    /// Returns an error if a name is not valid, see [`Workspace::validate_name`], or an
+    /// `AlreadyExists` error if two workspaces have the same name. Surrounding whitespace
+    /// is removed from the names.
+    ///
+    /// * `workspaces`: A list of workspaces to add to the workspace manager.
+    pub fn without_current_workspace(
+        workspaces: Vec<Workspace>,
+    ) -> Result<WorkspaceManager, WorkspaceError> {
+        let mut workspace_map = HashMap::with_capacity(workspaces.len());
+
+        for mut workspace in workspaces {
+            workspace.name = Workspace::validate_name(&workspace.name)?.to_string();
+
+            if workspace_map.contains_key(&workspace.name) {
+                return Err(WorkspaceError::AlreadyExists(workspace.name));
+            }
+            workspace_map.insert(workspace.name.clone(), workspace);
         }
-    }
 
-    /// Returns the current workspace.
-    pub fn get_current_workspace(&self) -> &'a Workspace {
-        self.workspaces.get(self.current_workspace).unwrap()
+        Ok(WorkspaceManager {
+            current_workspace: None,
+            default_workspace: None,
+            workspaces: workspace_map,
+            case_insensitive: false,
+            fallback_workspace: None,
+            recent_workspaces: VecDeque::new(),
+            recent_limit: DEFAULT_RECENT_LIMIT,
+            aliases: HashMap::new(),
+            create_hooks: Hooks::default(),
+            change_hooks: Hooks::default(),
+        })
     }
 
-    /// Adds a workspace to the list of workspaces.
-    /// Overwrites any existing workspace with the same name.
-    ///
-    /// * `workspace`: The workspace to add to the list of workspaces.
-    pub fn add_workspace(&mut self, workspace: &'a Workspace) {
-        self.workspaces.insert(&workspace.name, workspace);
+    /// Returns the key under which the workspace with the given name is stored,
+    /// matching case-insensitively if the manager is in case-insensitive mode
+    /// and falling back to the aliases added with [`WorkspaceManager::add_alias`].
+    pub(crate) fn lookup_key<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        self.lookup_name(name).or_else(|| {
+            self.aliases
+                .get(name)
+                .and_then(|target| self.lookup_name(target))
+        })
     }
-}
 
-mod tests {
-    #[allow(unused_imports)]
-    use super::*;
+    /// Returns the name of a workspace that differs from the given name only in case,
+    /// if the manager is in case-insensitive mode and has one.
+    fn case_collision(&self, name: &str) -> Option<&str> {
+        let name_lower = name.to_lowercase();
 
-    #[test]
-    fn test_workspace_manager_from_single_workspace() {
-        let workspace = Workspace {
-            name: "example name".to_string(),
-            path: "~/some/path".into(),
-        };
+        self.workspaces
+            .keys()
+            .filter(|_| self.case_insensitive)
+            .find(|key| *key != name && key.to_lowercase() == name_lower)
+            .map(String::as_str)
+    }
 
-        let workspace_manager = WorkspaceManager::from_single_workspace(&workspace);
-        assert_eq!(workspace_manager.current_workspace, &workspace.name);
+    fn lookup_name<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        if self.workspaces.contains_key(name) {
+            Some(name)
+        } else if self.case_insensitive {
+            self.workspaces
+                .keys()
+                .find(|key| key.to_lowercase() == name.to_lowercase())
+                .map(String::as_str)
+        } else {
+            None
+        }
     }
 
-    #[test]
-    fn test_workspace_manager_new() {
-        let workspace1 = Workspace {
-            name: "example name".to_string(),
-            path: "~/some/path".into(),
-        };
+    /// Adds an alias for a workspace, e.g. `w` for `work-notes`, which is accepted
+    /// wherever a workspace name is looked up, like [`WorkspaceManager::get_workspace`]
+    /// and [`WorkspaceManager::set_current_workspace`]. An existing alias is replaced.
+    ///
+    /// Returns an error if the alias is not a valid name, is already the name of
+    /// a workspace or if no workspace is named `name`. A workspace added later with
+    /// the same name as an alias shadows the alias.
+    ///
+    /// * `alias`: The alias of the workspace.
+    /// * `name`: The name of the workspace.
+    pub fn add_alias(
+        &mut self,
+        alias: impl Into<String>,
+        name: &str,
+    ) -> Result<(), WorkspaceError> {
+        let alias = Workspace::validate_name(&alias.into())?.to_string();
 
-        let workspace2 = Workspace {
-            name: "another example name".to_string(),
-            path: "~/another/path".into(),
-        };
+        if self.lookup_name(&alias).is_some() {
+            return Err(WorkspaceError::AlreadyExists(alias));
+        }
 
-        let workspace_manager =
-            WorkspaceManager::new(vec![&workspace1, &workspace2], &workspace1.name).expect("");
-        assert_eq!(workspace_manager.current_workspace, &workspace1.name);
+        let name = self
+            .lookup_name(name)
+            .ok_or_else(|| WorkspaceNotFound {
+                workspace: name.to_string(),
+            })?
+            .to_string();
+        self.aliases.insert(alias, name);
+
+        Ok(())
     }
 
-    #[test]
-    fn test_workspace_manager_current_workspace() {
-        let workspace1 = Workspace {
-            name: "example name".to_string(),
-            path: "~/some/path".into(),
-        };
+    /// Removes an alias added with [`WorkspaceManager::add_alias`],
+    /// returning the name of the workspace it referred to.
+    ///
+    /// * `alias`: The alias to remove.
+    pub fn remove_alias(&mut self, alias: &str) -> Option<String> {
+        self.aliases.remove(alias)
+    }
 
-        let workspace2 = Workspace {
-            name: "another example name".to_string(),
-            path: "~/another/path".into(),
-        };
+    /// Returns a workspace with the given name, or None if it doesn't exist.
+    /// The name is matched case-sensitively unless the manager was constructed
+    /// with [`WorkspaceManager::new_case_insensitive`].
+    ///
+    /// * `name`: The name of the workspace.
+    pub fn get_workspace(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.get(self.lookup_key(name)?)
+    }
 
-        let mut workspace_manager =
-            WorkspaceManager::new(vec![&workspace1, &workspace2], &workspace1.name)
-                .expect("Workspace 1 not found!");
+    /// Returns a workspace whose name matches the given name case-insensitively,
+    /// regardless of the mode the manager was constructed in.
+    /// An exact match is preferred if several names differ only in case.
+    ///
+    /// * `name`: The name of the workspace, in any case.
+    pub fn get_workspace_ci(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.get(name).or_else(|| {
+            self.workspaces
+                .values()
+                .find(|w| w.name.to_lowercase() == name.to_lowercase())
+        })
+    }
+
+    /// Returns the number of registered workspaces.
+    pub fn len(&self) -> usize {
+        self.workspaces.len()
+    }
+
+    /// Returns true if no workspaces are registered.
+    pub fn is_empty(&self) -> bool {
+        self.workspaces.is_empty()
+    }
+
+    /// Returns an iterator over the names of all workspaces, in arbitrary order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.workspaces.keys().map(String::as_str)
+    }
+
+    /// Returns the names of all workspaces, sorted lexicographically.
+    pub fn names_sorted(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.names().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns all workspaces whose name starts with `prefix`, sorted by name,
+    /// e.g. to complete a partially typed workspace name.
+    ///
+    /// * `prefix`: The beginning of the workspace name.
+    /// * `case_sensitive`: Whether the case must match; if not, `No` matches `notes` as well.
+    pub fn workspaces_with_name_prefix(
+        &self,
+        prefix: &str,
+        case_sensitive: bool,
+    ) -> Vec<&Workspace> {
+        let prefix_lower = prefix.to_lowercase();
+        let mut workspaces: Vec<&Workspace> = self
+            .workspaces
+            .values()
+            .filter(|w| {
+                if case_sensitive {
+                    w.name.starts_with(prefix)
+                } else {
+                    w.name.to_lowercase().starts_with(&prefix_lower)
+                }
+            })
+            .collect();
+
+        workspaces.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        workspaces
+    }
+
+    /// Returns an iterator over all workspaces, in arbitrary order.
+    /// `for workspace in &workspace_manager` iterates the same way.
+    pub fn workspaces(&self) -> impl Iterator<Item = &Workspace> {
+        self.workspaces.values()
+    }
+
+    /// Returns the workspace containing the given path, or None if no workspace contains it.
+    /// Both the path and the workspace roots are canonicalized before being compared,
+    /// so symlinks and `..` components are resolved, and on platforms whose filesystems
+    /// ignore case the comparison does too, see [`Workspace::contains`]. If several (nested) workspaces
+    /// contain the path, the most specific one is returned.
+    ///
+    /// If no workspace contains the path, the fallback workspace set with
+    /// [`WorkspaceManager::set_fallback_workspace`] is returned, if any.
+    ///
+    /// * `path`: The path to find the workspace of. It does not need to exist.
+    pub fn workspace_for_path(&self, path: &Path) -> Option<&Workspace> {
+        canonicalize_lenient(path)
+            .ok()
+            .and_then(|path| {
+                self.workspaces
+                    .values()
+                    .filter_map(|w| {
+                        let root = canonicalize_lenient(&w.path).ok()?;
+                        starts_with_case(&path, &root, CASE_INSENSITIVE_FS)
+                            .then(|| (root.components().count(), w))
+                    })
+                    .max_by_key(|(depth, _)| *depth)
+                    .map(|(_, w)| w)
+            })
+            .or_else(|| self.get_fallback_workspace())
+    }
+
+    /// Designates a workspace as the catch-all for paths that don't belong to any
+    /// workspace, like the conventional `default` workspace of Neorg.
+    /// Returns a WorkspaceNotFound error if the workspace doesn't exist.
+    ///
+    /// * `name`: The name of the fallback workspace.
+    pub fn set_fallback_workspace(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<(), WorkspaceNotFound> {
+        let name = name.into();
+
+        match self.lookup_key(&name) {
+            Some(key) => {
+                self.fallback_workspace = Some(key.to_string());
+                Ok(())
+            }
+            None => Err(WorkspaceNotFound { workspace: name }),
+        }
+    }
+
+    /// Returns the fallback workspace, or None if there is no fallback workspace.
+    pub fn get_fallback_workspace(&self) -> Option<&Workspace> {
+        self.workspaces.get(self.fallback_workspace.as_ref()?)
+    }
+
+    /// Unsets the fallback workspace, so that paths outside of all workspaces
+    /// no longer belong to any workspace.
+    pub fn clear_fallback_workspace(&mut self) {
+        self.fallback_workspace = None;
+    }
+
+    /// Sets the current workspace to the workspace with the given name.
+    /// Returns unit if the workspace was set, else returns a WorkspaceNotFound error.
+    ///
+    /// * `name`: The name of the workspace to set as the current workspace.
+    pub fn set_current_workspace(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<(), WorkspaceNotFound> {
+        let name = name.into();
+
+        match self.lookup_key(&name) {
+            Some(key) => {
+                let key = key.to_string();
+
+                self.recent_workspaces.retain(|recent| *recent != key);
+                self.recent_workspaces.push_front(key.clone());
+                self.recent_workspaces.truncate(self.recent_limit);
+
+                let previous = self.current_workspace.replace(key.clone());
+
+                if previous.as_ref() != Some(&key) {
+                    let event = CurrentWorkspaceChanged {
+                        from: previous.as_deref(),
+                        to: &key,
+                    };
+
+                    for hook in &self.change_hooks.0 {
+                        hook(&event);
+                    }
+                }
+
+                Ok(())
+            }
+            None => Err(WorkspaceNotFound { workspace: name }),
+        }
+    }
+
+    /// Registers a callback invoked whenever [`WorkspaceManager::set_current_workspace`]
+    /// (or a method built on it, like [`WorkspaceManager::reset_to_default`]) makes a
+    /// different workspace current, e.g. to update a status line.
+    /// Callbacks are invoked in registration order and must be thread-safe.
+    ///
+    /// * `f`: The callback to invoke.
+    pub fn on_workspace_change(&mut self, f: Box<WorkspaceChangeHook>) {
+        self.change_hooks.0.push(Arc::from(f));
+    }
+
+    /// Returns the names of the workspaces most recently made current through
+    /// [`WorkspaceManager::set_current_workspace`], most recent first.
+    /// At most [`DEFAULT_RECENT_LIMIT`] workspaces are remembered unless changed with
+    /// [`WorkspaceManager::set_recent_limit`].
+    pub fn recent_workspaces(&self) -> Vec<&str> {
+        self.recent_workspaces.iter().map(String::as_str).collect()
+    }
+
+    /// Sets how many workspaces [`WorkspaceManager::recent_workspaces`] remembers,
+    /// forgetting the oldest ones if there are more already.
+    ///
+    /// * `limit`: The maximum number of remembered workspaces.
+    pub fn set_recent_limit(&mut self, limit: usize) {
+        self.recent_limit = limit;
+        self.recent_workspaces.truncate(limit);
+    }
+
+    /// Sets the current workspace like [`WorkspaceManager::set_current_workspace`],
+    /// returning the name of the previous current workspace, or None if there was none.
+    /// On error the current workspace is left unchanged.
+    ///
+    /// * `name`: The name of the workspace to set as the current workspace.
+    pub fn swap_current_workspace(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<Option<String>, WorkspaceNotFound> {
+        let previous = self.current_workspace.clone();
+        self.set_current_workspace(name)?;
+
+        Ok(previous)
+    }
+
+    /// Designates a workspace as the configured default, without changing the current workspace.
+    /// Returns a WorkspaceNotFound error if the workspace doesn't exist.
+    ///
+    /// * `name`: The name of the default workspace.
+    pub fn set_default_workspace(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<(), WorkspaceNotFound> {
+        let name = name.into();
+
+        match self.lookup_key(&name) {
+            Some(key) => {
+                self.default_workspace = Some(key.to_string());
+                Ok(())
+            }
+            None => Err(WorkspaceNotFound { workspace: name }),
+        }
+    }
+
+    /// Returns the default workspace, or None if there is no default workspace.
+    pub fn get_default_workspace(&self) -> Option<&Workspace> {
+        self.workspaces.get(self.default_workspace.as_ref()?)
+    }
+
+    /// Makes the default workspace the current workspace again, returning it.
+    /// If there is no default workspace, the current workspace is left unchanged
+    /// and None is returned.
+    pub fn reset_to_default(&mut self) -> Option<&Workspace> {
+        let default_workspace = self.default_workspace.clone()?;
+        self.set_current_workspace(default_workspace).ok()?;
+
+        self.get_current_workspace()
+    }
+
+    /// Returns the current workspace, or None if there is no current workspace.
+    pub fn get_current_workspace(&self) -> Option<&Workspace> {
+        self.workspaces.get(self.current_workspace.as_ref()?)
+    }
+
+    /// Returns the name of the current workspace without looking up the workspace itself,
+    /// or None if there is no current workspace.
+    pub fn current_workspace_name(&self) -> Option<&str> {
+        self.current_workspace.as_deref()
+    }
+
+    /// Resolves a note name relative to the current workspace, see [`Workspace::file_path`].
+    /// Returns None if there is no current workspace or the name is not a valid note path.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn current_file_path(&self, rel: &str) -> Option<PathBuf> {
+        self.get_current_workspace()?.file_path(rel)
+    }
+
+    /// Unsets the current workspace, e.g. when the user opens a file outside of any workspace.
+    pub fn clear_current_workspace(&mut self) {
+        self.current_workspace = None;
+    }
+
+    /// Adds a workspace to the list of workspaces.
+    /// Overwrites any existing workspace with the same name.
+    /// Returns an error if the name is not valid, see [`Workspace::validate_name`];
+    /// surrounding whitespace is removed from the name. In case-insensitive mode an
+    /// `AlreadyExists` error is returned if the name differs from an existing one only in case.
+    ///
+    /// * `workspace`: The workspace to add to the list of workspaces.
+    pub fn add_workspace(&mut self, mut workspace: Workspace) -> Result<(), WorkspaceError> {
+        workspace.name = Workspace::validate_name(&workspace.name)?.to_string();
+        if self.case_collision(&workspace.name).is_some() {
+            return Err(WorkspaceError::AlreadyExists(workspace.name));
+        }
+        self.workspaces.insert(workspace.name.clone(), workspace);
+
+        Ok(())
+    }
+
+    /// Returns the workspace with the given name, first adding a new workspace with the given
+    /// path if none exists. The path of an existing workspace is left unchanged,
+    /// and the current workspace is never changed.
+    /// Returns an error if the name is not valid, see [`Workspace::validate_name`].
+    ///
+    /// * `name`: The name of the workspace.
+    /// * `path`: The path of the workspace to add if it doesn't exist yet.
+    pub fn get_or_create(
+        &mut self,
+        name: &str,
+        path: PathBuf,
+    ) -> Result<&Workspace, WorkspaceError> {
+        let name = Workspace::validate_name(name)?;
+        let name = self.lookup_key(name).unwrap_or(name).to_string();
+
+        Ok(self
+            .workspaces
+            .entry(name.clone())
+            .or_insert_with(|| Workspace::with_path(name, path)))
+    }
+
+    /// Renames a workspace, keeping it as the current workspace if it was the current one.
+    /// Returns an error if no workspace is named `old`, if `new` is not a valid name,
+    /// if a different workspace is already named `new` or if `new` is an alias.
+    /// Surrounding whitespace is removed from `new`. Both names are matched like in
+    /// [`WorkspaceManager::get_workspace`], so `old` may be an alias and in case-insensitive
+    /// mode a workspace can change the case of its name but not take the name of another
+    /// one in another case.
+    ///
+    /// * `old`: The current name of the workspace.
+    /// * `new`: The new name of the workspace.
+    pub fn rename_workspace(&mut self, old: &str, new: String) -> Result<(), WorkspaceError> {
+        let new = Workspace::validate_name(&new)?.to_string();
+        let old = self
+            .lookup_key(old)
+            .ok_or_else(|| WorkspaceNotFound {
+                workspace: old.to_string(),
+            })?
+            .to_string();
+        let old = old.as_str();
+
+        if self.aliases.contains_key(&new)
+            || self
+                .lookup_name(&new)
+                .is_some_and(|existing| existing != old)
+        {
+            return Err(WorkspaceError::AlreadyExists(new));
+        }
+
+        let mut workspace = self
+            .workspaces
+            .remove(old)
+            .expect("the workspace was looked up above");
+
+        if self.current_workspace.as_deref() == Some(old) {
+            self.current_workspace = Some(new.clone());
+        }
+        if self.default_workspace.as_deref() == Some(old) {
+            self.default_workspace = Some(new.clone());
+        }
+        if self.fallback_workspace.as_deref() == Some(old) {
+            self.fallback_workspace = Some(new.clone());
+        }
+        for reference in self
+            .recent_workspaces
+            .iter_mut()
+            .chain(self.aliases.values_mut())
+            .filter(|reference| *reference == old)
+        {
+            *reference = new.clone();
+        }
+
+        workspace.name = new.clone();
+        self.workspaces.insert(new, workspace);
+
+        Ok(())
+    }
+
+    /// Merges the workspaces of another workspace manager into this one.
+    /// The current, default and fallback workspaces stay unchanged unless this manager has none,
+    /// in which case the other manager's are used. Aliases of the other manager are added
+    /// unless this manager already uses the alias.
+    ///
+    /// Identical workspaces are never considered a conflict. With [`MergePolicy::Error`]
+    /// an `AlreadyExists` error is returned on the first conflicting name and
+    /// this manager is left untouched. Whatever the policy, the same happens in
+    /// case-insensitive mode for names differing from another one only in case.
+    ///
+    /// * `other`: The workspace manager to merge into this one.
+    /// * `policy`: How to resolve workspaces with the same name.
+    pub fn merge(
+        &mut self,
+        other: WorkspaceManager,
+        policy: MergePolicy,
+    ) -> Result<(), WorkspaceError> {
+        if self.case_insensitive {
+            let mut seen: HashMap<String, &str> = self
+                .workspaces
+                .keys()
+                .map(|name| (name.to_lowercase(), name.as_str()))
+                .collect();
+
+            for name in other.workspaces.keys() {
+                if let Some(existing) = seen.insert(name.to_lowercase(), name) {
+                    if existing != name {
+                        return Err(WorkspaceError::AlreadyExists(name.clone()));
+                    }
+                }
+            }
+        }
+
+        if policy == MergePolicy::Error {
+            if let Some(conflict) = other.workspaces.values().find(|w| {
+                self.workspaces
+                    .get(&w.name)
+                    .is_some_and(|existing| existing != *w)
+            }) {
+                return Err(WorkspaceError::AlreadyExists(conflict.name.clone()));
+            }
+        }
+
+        for (name, workspace) in other.workspaces {
+            if policy != MergePolicy::PreferSelf || !self.workspaces.contains_key(&name) {
+                self.workspaces.insert(name, workspace);
+            }
+        }
+
+        for (alias, name) in other.aliases {
+            if self.lookup_name(&alias).is_none() {
+                self.aliases.entry(alias).or_insert(name);
+            }
+        }
+
+        if self.current_workspace.is_none() {
+            self.current_workspace = other.current_workspace;
+        }
+        if self.default_workspace.is_none() {
+            self.default_workspace = other.default_workspace;
+        }
+        if self.fallback_workspace.is_none() {
+            self.fallback_workspace = other.fallback_workspace;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all workspaces, e.g. before reloading the configuration.
+    /// Afterwards there is no current, default or fallback workspace, so
+    /// [`WorkspaceManager::get_current_workspace`] returns None until a new workspace is
+    /// added and made current, and the lists of recent workspaces and aliases are empty.
+    pub fn clear(&mut self) {
+        self.workspaces.clear();
+        self.current_workspace = None;
+        self.default_workspace = None;
+        self.fallback_workspace = None;
+        self.recent_workspaces.clear();
+        self.aliases.clear();
+    }
+
+    /// Removes the workspace with the given name, returning it if it existed.
+    /// The current workspace cannot be removed - switch to another workspace first.
+    /// Removing the default or fallback workspace unsets the default or fallback,
+    /// and the aliases of the workspace are removed along with it.
+    /// The name is matched like in [`WorkspaceManager::get_workspace`], so it may be an alias.
+    ///
+    /// * `name`: The name of the workspace to remove.
+    pub fn remove_workspace(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<Workspace>, WorkspaceIsCurrent> {
+        let Some(name) = self.lookup_key(name).map(str::to_string) else {
+            return Ok(None);
+        };
+        let name = name.as_str();
+
+        if self.current_workspace.as_deref() == Some(name) {
+            Err(WorkspaceIsCurrent {
+                workspace: name.to_string(),
+            })
+        } else {
+            if self.default_workspace.as_deref() == Some(name) {
+                self.default_workspace = None;
+            }
+            if self.fallback_workspace.as_deref() == Some(name) {
+                self.fallback_workspace = None;
+            }
+            self.recent_workspaces.retain(|recent| recent != name);
+            self.aliases.retain(|_, target| target != name);
+
+            Ok(self.workspaces.remove(name))
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a WorkspaceManager {
+    type Item = &'a Workspace;
+    type IntoIter = std::collections::hash_map::Values<'a, String, Workspace>;
+
+    /// Iterates over all workspaces in arbitrary order, like [`WorkspaceManager::workspaces`].
+    fn into_iter(self) -> Self::IntoIter {
+        self.workspaces.values()
+    }
+}
+
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_workspace_display() {
+        let workspace = Workspace::with_path("example name", "/some/path");
+
+        assert_eq!(workspace.to_string(), "example name (/some/path)");
+        assert_eq!(workspace.path(), Path::new("/some/path"));
+    }
+
+    #[test]
+    fn test_workspace_from_template() {
+        let vars = HashMap::from([("project".to_string(), "dirman".to_string())]);
+
+        let workspace = Workspace::from_template("work", "/work/{project}/notes", &vars)
+            .expect("Unable to expand the template");
+        assert_eq!(workspace.path, PathBuf::from("/work/dirman/notes"));
+        assert_eq!(
+            Workspace::from_template("work", "/work/{other}", &vars),
+            Err(InvalidWorkspace::Path(ExpandError::UnknownPlaceholder(
+                "other".to_string()
+            )))
+        );
+        assert_eq!(
+            Workspace::from_template(" ", "/work/{project}", &vars),
+            Err(InvalidWorkspace::Name(NameError::Empty))
+        );
+    }
+
+    #[test]
+    fn test_workspace_resolve_relative_to() {
+        let base = Path::new("/config");
+        let resolve = |path: &str| {
+            Workspace::with_path("notes", path)
+                .resolve_relative_to(base)
+                .path
+        };
+
+        assert_eq!(resolve("notes"), PathBuf::from("/config/notes"));
+        assert_eq!(resolve("../notes"), PathBuf::from("/config/../notes"));
+        assert_eq!(resolve("/notes"), PathBuf::from("/notes"));
+        assert_eq!(resolve("~/notes"), PathBuf::from("~/notes"));
+        assert_eq!(resolve("$HOME/notes"), PathBuf::from("$HOME/notes"));
+    }
+
+    #[test]
+    fn test_workspace_with_path() {
+        let path = PathBuf::from("/some/path");
+        let workspace = Workspace::with_path("example name", path.clone());
+
+        assert_eq!(workspace.name, "example name");
+        assert_eq!(workspace.path, path);
+        assert_eq!(
+            workspace,
+            Workspace::new("example name".to_string(), "/some/path").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_workspace_validate_name() {
+        assert_eq!(Workspace::validate_name(" notes "), Ok("notes"));
+        assert_eq!(Workspace::validate_name("my notes"), Ok("my notes"));
+        assert_eq!(Workspace::validate_name(" \t"), Err(NameError::Empty));
+        assert_eq!(
+            Workspace::validate_name("a/b"),
+            Err(NameError::Separator("a/b".to_string()))
+        );
+        assert_eq!(
+            Workspace::validate_name("a\\b"),
+            Err(NameError::Separator("a\\b".to_string()))
+        );
+        assert_eq!(
+            Workspace::validate_name("a\u{7}b"),
+            Err(NameError::ControlCharacter("a\u{7}b".to_string()))
+        );
+
+        assert_eq!(
+            Workspace::new(" notes ".to_string(), "/notes")
+                .unwrap()
+                .name,
+            "notes"
+        );
+        assert_eq!(
+            Workspace::new("".to_string(), "/notes").err(),
+            Some(InvalidWorkspace::Name(NameError::Empty))
+        );
+    }
+
+    #[test]
+    fn test_workspace_strip_prefix() {
+        let workspace = Workspace::with_path("example name", "test/example_workspace");
+        let absolute = std::path::absolute("test/example_workspace").unwrap();
+
+        assert_eq!(
+            workspace.strip_prefix(Path::new("test/example_workspace/projects/todo.norg")),
+            Some(PathBuf::from("projects/todo.norg"))
+        );
+        assert_eq!(
+            workspace.strip_prefix(&absolute.join("../example_workspace/file1.norg")),
+            Some(PathBuf::from("file1.norg"))
+        );
+        assert_eq!(
+            workspace.strip_prefix(&absolute.join("../other.norg")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_workspace_contains() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let notes = dir.path().join("notes");
+        std::fs::create_dir_all(&notes).expect("Unable to create the workspace");
+        let workspace = Workspace::with_path("notes", &notes);
+
+        assert!(workspace.contains(&notes.join("projects/todo.norg")));
+        assert!(workspace.contains(&notes));
+        assert!(!workspace.contains(&dir.path().join("notes2/todo.norg")));
+        assert!(!workspace.contains(&notes.join("../todo.norg")));
+
+        // Only filesystems that ignore case treat a differently cased path as the same.
+        assert_eq!(
+            workspace.contains(&dir.path().join("NoTeS/todo.norg")),
+            CASE_INSENSITIVE_FS
+        );
+    }
+
+    #[test]
+    fn test_workspace_same_location_as() {
+        let workspace = Workspace::with_path("relative", "test/example_workspace");
+        let absolute = Workspace::with_path(
+            "absolute",
+            std::path::absolute("test/example_workspace/../example_workspace").unwrap(),
+        );
+        let missing = Workspace::with_path("missing", "test/this_workspace_does_not_exist");
+
+        assert!(workspace.same_location_as(&absolute));
+        assert!(!workspace.same_location_as(&missing));
+        assert!(!missing.same_location_as(&missing));
+    }
+
+    #[test]
+    fn test_workspace_extensions() {
+        let workspace = Workspace::with_path("example name", "/some/path");
+        assert_eq!(workspace.extensions(), vec!["norg"]);
+        assert!(workspace.is_note(Path::new("todo.norg")));
+        assert!(!workspace.is_note(Path::new("todo.md")));
+
+        let workspace = workspace.with_extensions(vec!["md".to_string(), "norg".to_string()]);
+        assert_eq!(
+            workspace.file_path("todo"),
+            Some(PathBuf::from("/some/path/todo.md"))
+        );
+        assert!(workspace.is_note(Path::new("todo.norg")));
+        assert!(workspace.is_note(Path::new("todo.md")));
+    }
+
+    #[test]
+    fn test_workspace_metadata() {
+        let mut workspace = Workspace::new("notes".to_string(), "/notes")
+            .unwrap()
+            .with_metadata("template", "default.norg");
+
+        assert_eq!(workspace.get_meta("template"), Some("default.norg"));
+        assert_eq!(workspace.get_meta("journal"), None);
+        assert_eq!(
+            workspace.set_meta("template", "meeting.norg"),
+            Some("default.norg".to_string())
+        );
+
+        // Metadata does not take part in equality.
+        assert_eq!(
+            workspace,
+            Workspace::new("notes".to_string(), "/notes").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_workspace_canonical_path() {
+        let workspace = Workspace::with_path(
+            "example name",
+            "test/example_workspace/../example_workspace",
+        );
+
+        assert!(workspace.exists());
+        assert_eq!(
+            workspace.canonical_path().unwrap(),
+            Path::new("test/example_workspace").canonicalize().unwrap()
+        );
+
+        let workspace = Workspace::with_path("example name", "test/this_workspace_does_not_exist");
+
+        assert!(!workspace.exists());
+        assert!(workspace.canonical_path().is_err());
+        assert_eq!(
+            workspace.normalized_path(),
+            Path::new("test/this_workspace_does_not_exist")
+        );
+    }
+
+    #[test]
+    fn test_workspace_eq_hash() {
+        let workspace = Workspace::with_path("example name", "/some/path");
+        let other_path = Workspace {
+            path: "/other/path".into(),
+            ..workspace.clone()
+        };
+
+        let set: std::collections::HashSet<Workspace> =
+            [workspace.clone(), workspace.clone(), other_path.clone()].into();
+        assert_eq!(set.len(), 2);
+        assert_ne!(workspace, other_path);
+    }
+
+    #[test]
+    fn test_workspace_file_path() {
+        let workspace = Workspace::with_path("example name", "/some/path");
+
+        assert_eq!(
+            workspace.file_path("projects/todo"),
+            Some(PathBuf::from("/some/path/projects/todo.norg"))
+        );
+        assert_eq!(
+            workspace.file_path("./todo.norg"),
+            Some(PathBuf::from("/some/path/todo.norg"))
+        );
+        assert_eq!(workspace.file_path("../escape"), None);
+        assert_eq!(workspace.file_path("projects/../../escape"), None);
+        assert_eq!(workspace.file_path("/etc/passwd"), None);
+        assert_eq!(workspace.file_path(""), None);
+    }
+
+    #[test]
+    fn test_workspace_manager_from_single_workspace() {
+        let workspace = Workspace::with_path("example name", "~/some/path");
+
+        let workspace_manager = WorkspaceManager::from_single_workspace(workspace.clone()).unwrap();
+        assert_eq!(
+            workspace_manager.current_workspace_name(),
+            Some(workspace.name.as_str())
+        );
+    }
+
+    #[test]
+    fn test_workspace_new() {
+        let workspace = Workspace::new("example name".to_string(), "/some/path")
+            .expect("Unable to expand the workspace path");
+        assert_eq!(workspace.path, PathBuf::from("/some/path"));
+
+        let workspace = Workspace::new("example name".to_string(), "~/some/path")
+            .expect("Unable to expand the workspace path");
+        assert!(!workspace.path.starts_with("~"));
+        assert!(workspace.path.ends_with("some/path"));
+    }
+
+    #[test]
+    fn test_workspace_manager_new() {
+        let workspace1 = Workspace::with_path("example name", "~/some/path");
+
+        let workspace2 = Workspace::with_path("another example name", "~/another/path");
+
+        let workspace_manager =
+            WorkspaceManager::new(vec![workspace1.clone(), workspace2], &workspace1.name)
+                .expect("");
+        assert_eq!(
+            workspace_manager.current_workspace_name(),
+            Some(workspace1.name.as_str())
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_new_missing_default() {
+        let workspace = Workspace::with_path("example name", "~/some/path");
+
+        let err = WorkspaceManager::new(vec![workspace], "missing")
+            .expect_err("A missing default workspace must be rejected");
+        assert_eq!(
+            err,
+            WorkspaceError::NotFound(WorkspaceNotFound {
+                workspace: "missing".to_string()
+            })
+        );
+        assert_eq!(err.to_string(), r#"workspace "missing" not found"#);
+    }
+
+    #[test]
+    fn test_workspace_manager_new_invalid_names() {
+        let workspace_manager =
+            WorkspaceManager::new(vec![Workspace::with_path(" notes ", "/notes")], "notes")
+                .expect("Surrounding whitespace must be trimmed");
+        assert_eq!(workspace_manager.names_sorted(), vec!["notes"]);
+
+        assert_eq!(
+            WorkspaceManager::from_single_workspace(Workspace::with_path("", "/notes")).err(),
+            Some(WorkspaceError::InvalidName(NameError::Empty))
+        );
+        assert_eq!(
+            WorkspaceManager::without_current_workspace(vec![
+                Workspace::with_path("notes", "/notes"),
+                Workspace::with_path("notes ", "/other"),
+            ])
+            .err(),
+            Some(WorkspaceError::AlreadyExists("notes".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_current_workspace() {
+        let workspace1 = Workspace::with_path("example name", "~/some/path");
+
+        let workspace2 = Workspace::with_path("another example name", "~/another/path");
+
+        let mut workspace_manager = WorkspaceManager::new(
+            vec![workspace1.clone(), workspace2.clone()],
+            &workspace1.name,
+        )
+        .expect("Workspace 1 not found!");
         workspace_manager
             .set_current_workspace(&workspace2.name)
             .expect("Unable to set the current workspace");
 
         assert_eq!(
-            &workspace_manager.get_current_workspace().name,
+            &workspace_manager.get_current_workspace().unwrap().name,
             &workspace2.name
         );
     }
+
+    #[test]
+    fn test_workspace_manager_names() {
+        let workspace1 = Workspace::with_path("b", "~/b");
+
+        let workspace2 = Workspace::with_path("a", "~/a");
+
+        let workspace_manager =
+            WorkspaceManager::new(vec![workspace1.clone(), workspace2], &workspace1.name)
+                .expect("Workspace 1 not found!");
+
+        assert_eq!(workspace_manager.names_sorted(), vec!["a", "b"]);
+        assert_eq!(workspace_manager.names().count(), 2);
+        assert!(workspace_manager
+            .workspaces()
+            .any(|w| w.path == Path::new("~/a")));
+    }
+
+    #[test]
+    fn test_workspace_manager_workspaces_with_name_prefix() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("Norg", "/norg")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let names = |prefix, case_sensitive| -> Vec<&str> {
+            workspace_manager
+                .workspaces_with_name_prefix(prefix, case_sensitive)
+                .into_iter()
+                .map(|w| w.name.as_str())
+                .collect()
+        };
+
+        assert_eq!(names("no", true), vec!["notes"]);
+        assert_eq!(names("NO", false), vec!["Norg", "notes"]);
+        assert_eq!(names("", true), vec!["Norg", "notes", "work"]);
+        assert!(names("x", false).is_empty());
+    }
+
+    #[test]
+    fn test_workspace_manager_workspace_for_path() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let notes = dir.path().join("notes");
+        let nested = notes.join("nested");
+        std::fs::create_dir_all(&nested).expect("Unable to create the workspaces");
+
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", &notes)
+            .workspace("nested", &nested)
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let name_for = |path: &Path| {
+            workspace_manager
+                .workspace_for_path(path)
+                .map(|w| w.name.as_str())
+        };
+
+        assert_eq!(name_for(&notes.join("todo.norg")), Some("notes"));
+        assert_eq!(name_for(&nested.join("todo.norg")), Some("nested"));
+        assert_eq!(name_for(&nested.join("../todo.norg")), Some("notes"));
+        assert_eq!(name_for(&notes.join("../todo.norg")), None);
+
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&nested, &link).expect("Unable to create a symlink");
+            assert_eq!(name_for(&link.join("todo.norg")), Some("nested"));
+        }
+    }
+
+    #[test]
+    fn test_workspace_manager_current_file_path() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.current_file_path("projects/todo"),
+            Some(PathBuf::from("/notes/projects/todo.norg"))
+        );
+        assert_eq!(workspace_manager.current_file_path("../escape"), None);
+
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+
+        workspace_manager.clear_current_workspace();
+        assert_eq!(workspace_manager.current_file_path("todo"), None);
+        assert_eq!(workspace_manager.current_workspace_name(), None);
+    }
+
+    #[test]
+    fn test_workspace_manager_fallback_workspace() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("default", "/default")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let name_for = |workspace_manager: &WorkspaceManager, path: &str| {
+            workspace_manager
+                .workspace_for_path(Path::new(path))
+                .map(|w| w.name.clone())
+        };
+
+        assert_eq!(name_for(&workspace_manager, "/elsewhere/todo.norg"), None);
+        assert!(workspace_manager.set_fallback_workspace("missing").is_err());
+
+        workspace_manager
+            .set_fallback_workspace("default")
+            .expect("Unable to set the fallback workspace");
+        assert_eq!(
+            name_for(&workspace_manager, "/elsewhere/todo.norg").as_deref(),
+            Some("default")
+        );
+        assert_eq!(
+            name_for(&workspace_manager, "/notes/todo.norg").as_deref(),
+            Some("notes")
+        );
+
+        workspace_manager.remove_workspace("default").unwrap();
+        assert!(workspace_manager.get_fallback_workspace().is_none());
+    }
+
+    #[test]
+    fn test_workspace_manager_on_workspace_change() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&changes);
+        workspace_manager.on_workspace_change(Box::new(move |event| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((event.from.map(str::to_string), event.to.to_string()));
+        }));
+
+        workspace_manager.set_current_workspace("work").unwrap();
+        workspace_manager.set_current_workspace("work").unwrap();
+        assert!(workspace_manager.set_current_workspace("missing").is_err());
+        workspace_manager.clear_current_workspace();
+        workspace_manager.reset_to_default();
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                (Some("notes".to_string()), "work".to_string()),
+                (None, "notes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_recent_workspaces() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .workspace("journal", "/journal")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert!(workspace_manager.recent_workspaces().is_empty());
+
+        for name in ["work", "journal", "notes", "work"] {
+            workspace_manager.set_current_workspace(name).unwrap();
+        }
+        assert_eq!(
+            workspace_manager.recent_workspaces(),
+            vec!["work", "notes", "journal"]
+        );
+
+        workspace_manager.remove_workspace("journal").unwrap();
+        workspace_manager.set_recent_limit(1);
+        assert_eq!(workspace_manager.recent_workspaces(), vec!["work"]);
+    }
+
+    #[test]
+    fn test_workspace_manager_swap_current_workspace() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.swap_current_workspace("work"),
+            Ok(Some("notes".to_string()))
+        );
+        assert_eq!(
+            workspace_manager.swap_current_workspace("missing"),
+            Err(WorkspaceNotFound {
+                workspace: "missing".to_string()
+            })
+        );
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "work"
+        );
+
+        workspace_manager.clear_current_workspace();
+        assert_eq!(workspace_manager.swap_current_workspace("notes"), Ok(None));
+    }
+
+    #[test]
+    fn test_workspace_manager_default_workspace() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+        assert_eq!(
+            workspace_manager.get_default_workspace().unwrap().name,
+            "notes"
+        );
+
+        workspace_manager.set_current_workspace("work").unwrap();
+        assert_eq!(
+            workspace_manager.get_default_workspace().unwrap().name,
+            "notes"
+        );
+        assert_eq!(workspace_manager.reset_to_default().unwrap().name, "notes");
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+
+        assert!(workspace_manager.set_default_workspace("missing").is_err());
+        workspace_manager.set_default_workspace("work").unwrap();
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+
+        workspace_manager.set_current_workspace("work").unwrap();
+        workspace_manager.clear_current_workspace();
+        workspace_manager.remove_workspace("work").unwrap();
+        assert!(workspace_manager.get_default_workspace().is_none());
+        assert!(workspace_manager.reset_to_default().is_none());
+        assert_eq!(workspace_manager.current_workspace_name(), None);
+    }
+
+    #[test]
+    fn test_workspace_manager_clone() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let mut snapshot = workspace_manager.clone();
+        snapshot.set_current_workspace("work").unwrap();
+        snapshot.remove_workspace("notes").unwrap();
+
+        assert_eq!(workspace_manager.len(), 2);
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "notes"
+        );
+        assert_eq!(snapshot.names_sorted(), vec!["work"]);
+    }
+
+    #[test]
+    fn test_workspace_manager_get_or_create() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager
+                .get_or_create("notes", "/elsewhere".into())
+                .unwrap()
+                .path,
+            Path::new("/notes")
+        );
+        assert_eq!(
+            workspace_manager
+                .get_or_create(" work ", "/work".into())
+                .unwrap()
+                .path,
+            Path::new("/work")
+        );
+        assert!(workspace_manager.get_workspace("work").is_some());
+        assert_eq!(
+            workspace_manager.get_or_create("a/b", "/a/b".into()).err(),
+            Some(WorkspaceError::InvalidName(NameError::Separator(
+                "a/b".to_string()
+            )))
+        );
+        assert_eq!(workspace_manager.len(), 2);
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+    }
+
+    #[test]
+    fn test_workspace_manager_clear() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+        workspace_manager.set_fallback_workspace("work").unwrap();
+
+        workspace_manager.clear();
+        assert!(workspace_manager.is_empty());
+        assert!(workspace_manager.get_current_workspace().is_none());
+        assert!(workspace_manager.get_fallback_workspace().is_none());
+
+        workspace_manager
+            .add_workspace(Workspace::with_path(" notes ", "/notes"))
+            .unwrap();
+        assert!(workspace_manager.get_current_workspace().is_none());
+        assert_eq!(
+            workspace_manager.get_workspace("notes").unwrap().name,
+            "notes"
+        );
+        assert_eq!(
+            workspace_manager.add_workspace(Workspace::with_path(" ", "/empty")),
+            Err(WorkspaceError::InvalidName(NameError::Empty))
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_into_iter() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let mut names = Vec::new();
+        for workspace in &workspace_manager {
+            names.push(workspace.name.as_str());
+        }
+        names.sort_unstable();
+
+        assert_eq!(names, workspace_manager.names_sorted());
+    }
+
+    #[test]
+    fn test_workspace_manager_rename_workspace() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "~/notes")
+            .workspace("work", "~/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        workspace_manager
+            .rename_workspace("notes", "personal".to_string())
+            .expect("Unable to rename the workspace");
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "personal"
+        );
+        assert!(workspace_manager.get_workspace("notes").is_none());
+
+        assert_eq!(
+            workspace_manager.rename_workspace("personal", "work".to_string()),
+            Err(WorkspaceError::AlreadyExists("work".to_string()))
+        );
+        assert_eq!(
+            workspace_manager.rename_workspace("notes", "other".to_string()),
+            Err(WorkspaceError::NotFound(WorkspaceNotFound {
+                workspace: "notes".to_string()
+            }))
+        );
+        assert_eq!(
+            workspace_manager.rename_workspace("personal", "a\nb".to_string()),
+            Err(WorkspaceError::InvalidName(NameError::ControlCharacter(
+                "a\nb".to_string()
+            )))
+        );
+        workspace_manager
+            .rename_workspace("personal", " private ".to_string())
+            .unwrap();
+        assert!(workspace_manager.get_workspace("private").is_some());
+    }
+
+    #[test]
+    fn test_workspace_manager_merge() {
+        let system = || {
+            WorkspaceManager::builder()
+                .workspace("notes", "/system/notes")
+                .workspace("shared", "/system/shared")
+                .default("shared")
+                .build()
+                .expect("Unable to build the workspace manager")
+        };
+        let user = || {
+            WorkspaceManager::builder()
+                .workspace("notes", "/user/notes")
+                .build()
+                .expect("Unable to build the workspace manager")
+        };
+        let notes_path = |workspace_manager: &WorkspaceManager| {
+            workspace_manager
+                .get_workspace("notes")
+                .unwrap()
+                .path
+                .clone()
+        };
+
+        let mut merged = user();
+        merged
+            .merge(system(), MergePolicy::PreferSelf)
+            .expect("Unable to merge");
+        assert_eq!(merged.len(), 2);
+        assert_eq!(notes_path(&merged), Path::new("/user/notes"));
+        assert_eq!(merged.get_current_workspace().unwrap().name, "notes");
+
+        let mut merged = user();
+        merged
+            .merge(system(), MergePolicy::PreferOther)
+            .expect("Unable to merge");
+        assert_eq!(notes_path(&merged), Path::new("/system/notes"));
+
+        let mut merged = user();
+        assert_eq!(
+            merged.merge(system(), MergePolicy::Error),
+            Err(WorkspaceError::AlreadyExists("notes".to_string()))
+        );
+        assert_eq!(merged.len(), 1);
+
+        let mut merged = WorkspaceManager::without_current_workspace(Vec::new()).unwrap();
+        merged
+            .merge(system(), MergePolicy::Error)
+            .expect("Unable to merge");
+        assert_eq!(merged.get_current_workspace().unwrap().name, "shared");
+    }
+
+    #[test]
+    fn test_workspace_struct_literal() {
+        let workspace = Workspace {
+            name: "example name".to_string(),
+            path: PathBuf::from("/some/path"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            workspace,
+            Workspace::with_path("example name", "/some/path")
+        );
+        assert!(!workspace.read_only);
+        assert!(workspace.filesystem.is_none());
+    }
+
+    #[test]
+    fn test_workspace_ord() {
+        let workspaces = std::collections::BTreeSet::from([
+            Workspace::with_path("work", "/a"),
+            Workspace::with_path("notes", "/b"),
+            Workspace::with_path("notes", "/a"),
+        ]);
+
+        assert_eq!(
+            workspaces.into_iter().collect::<Vec<_>>(),
+            vec![
+                Workspace::with_path("notes", "/a"),
+                Workspace::with_path("notes", "/b"),
+                Workspace::with_path("work", "/a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_aliases() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("work-notes", "/work")
+            .workspace("notes", "/notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        workspace_manager
+            .add_alias("w", "work-notes")
+            .expect("Unable to add the alias");
+        assert_eq!(
+            workspace_manager
+                .get_workspace("w")
+                .map(|w| w.name.as_str()),
+            Some("work-notes")
+        );
+        workspace_manager.set_current_workspace("w").unwrap();
+        assert_eq!(
+            workspace_manager.current_workspace_name(),
+            Some("work-notes")
+        );
+
+        assert_eq!(
+            workspace_manager.add_alias("notes", "work-notes"),
+            Err(WorkspaceError::AlreadyExists("notes".to_string()))
+        );
+        assert!(matches!(
+            workspace_manager.add_alias("x", "missing"),
+            Err(WorkspaceError::NotFound(_))
+        ));
+
+        assert_eq!(
+            workspace_manager.rename_workspace("notes", "w".to_string()),
+            Err(WorkspaceError::AlreadyExists("w".to_string()))
+        );
+        workspace_manager
+            .rename_workspace("w", "work".to_string())
+            .unwrap();
+        assert_eq!(workspace_manager.current_workspace_name(), Some("work"));
+        assert_eq!(
+            workspace_manager
+                .get_workspace("w")
+                .map(|w| w.name.as_str()),
+            Some("work")
+        );
+
+        assert_eq!(
+            workspace_manager.remove_workspace("w").err(),
+            Some(WorkspaceIsCurrent {
+                workspace: "work".to_string()
+            })
+        );
+
+        assert_eq!(
+            workspace_manager.remove_alias("w"),
+            Some("work".to_string())
+        );
+        assert!(workspace_manager.get_workspace("w").is_none());
+
+        workspace_manager.add_alias("n", "notes").unwrap();
+        assert_eq!(
+            workspace_manager
+                .remove_workspace("n")
+                .unwrap()
+                .map(|w| w.name),
+            Some("notes".to_string())
+        );
+        assert!(workspace_manager.get_workspace("notes").is_none());
+        assert_eq!(workspace_manager.remove_alias("n"), None);
+    }
+
+    #[test]
+    fn test_workspace_manager_case_insensitive() {
+        let workspaces = vec![
+            Workspace::with_path("Notes", "/notes"),
+            Workspace::with_path("work", "/work"),
+        ];
+
+        let sensitive = WorkspaceManager::without_current_workspace(workspaces.clone()).unwrap();
+        assert!(sensitive.get_workspace("notes").is_none());
+        assert_eq!(sensitive.get_workspace_ci("notes").unwrap().name, "Notes");
+
+        let mut insensitive = WorkspaceManager::new_case_insensitive(workspaces.clone(), "NOTES")
+            .expect("Unable to create the workspace manager");
+        assert_eq!(insensitive.get_current_workspace().unwrap().name, "Notes");
+        assert_eq!(insensitive.get_workspace("WORK").unwrap().name, "work");
+        insensitive
+            .set_current_workspace("Work")
+            .expect("Unable to set the current workspace");
+        assert_eq!(insensitive.get_current_workspace().unwrap().name, "work");
+
+        assert_eq!(
+            insensitive.remove_workspace("WORK").err(),
+            Some(WorkspaceIsCurrent {
+                workspace: "work".to_string()
+            })
+        );
+        assert_eq!(
+            insensitive.rename_workspace("notes", "Work".to_string()),
+            Err(WorkspaceError::AlreadyExists("Work".to_string()))
+        );
+        insensitive
+            .rename_workspace("WORK", "Work".to_string())
+            .expect("Unable to change the case of the workspace name");
+        assert_eq!(insensitive.get_current_workspace().unwrap().name, "Work");
+
+        assert_eq!(
+            insensitive.add_workspace(Workspace::with_path("WORK", "/other")),
+            Err(WorkspaceError::AlreadyExists("WORK".to_string()))
+        );
+        insensitive
+            .add_workspace(Workspace::with_path("Work", "/other"))
+            .expect("Unable to replace a workspace with the same name");
+        assert_eq!(
+            insensitive.merge(
+                WorkspaceManager::without_current_workspace(vec![Workspace::with_path(
+                    "notes", "/other"
+                )])
+                .unwrap(),
+                MergePolicy::PreferOther
+            ),
+            Err(WorkspaceError::AlreadyExists("notes".to_string()))
+        );
+        assert_eq!(insensitive.get_workspace("notes").unwrap().name, "Notes");
+        assert_eq!(
+            insensitive
+                .remove_workspace("NOTES")
+                .expect("Unable to remove a non-current workspace")
+                .map(|w| w.name),
+            Some("Notes".to_string())
+        );
+
+        let mut colliding = workspaces;
+        colliding.push(Workspace::with_path("notes", "/other"));
+        assert_eq!(
+            WorkspaceManager::new_case_insensitive(colliding, "work").err(),
+            Some(WorkspaceError::AlreadyExists("notes".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_workspace_manager_remove_workspace() {
+        let workspace1 = Workspace::with_path("example name", "~/some/path");
+
+        let workspace2 = Workspace::with_path("another example name", "~/another/path");
+
+        let mut workspace_manager = WorkspaceManager::new(
+            vec![workspace1.clone(), workspace2.clone()],
+            &workspace1.name,
+        )
+        .expect("Workspace 1 not found!");
+
+        assert_eq!(
+            workspace_manager.remove_workspace(&workspace1.name).err(),
+            Some(WorkspaceIsCurrent {
+                workspace: workspace1.name.clone()
+            })
+        );
+
+        let removed = workspace_manager
+            .remove_workspace(&workspace2.name)
+            .expect("Unable to remove a non-current workspace");
+        assert_eq!(removed.map(|w| w.name), Some(workspace2.name.clone()));
+        assert!(workspace_manager.get_workspace(&workspace2.name).is_none());
+
+        assert!(workspace_manager
+            .remove_workspace(&workspace2.name)
+            .expect("Removing a missing workspace must not fail")
+            .is_none());
+    }
+
+    #[test]
+    fn test_workspace_manager_clear_current_workspace() {
+        let workspace = Workspace::with_path("example name", "~/some/path");
+
+        let mut workspace_manager =
+            WorkspaceManager::from_single_workspace(workspace.clone()).unwrap();
+        workspace_manager.clear_current_workspace();
+        assert!(workspace_manager.get_current_workspace().is_none());
+        assert!(workspace_manager.remove_workspace(&workspace.name).is_ok());
+
+        let workspace_manager =
+            WorkspaceManager::without_current_workspace(vec![workspace]).unwrap();
+        assert!(workspace_manager.get_current_workspace().is_none());
+        assert_eq!(workspace_manager.len(), 1);
+
+        let workspace_manager = WorkspaceManager::without_current_workspace(Vec::new()).unwrap();
+        assert!(workspace_manager.is_empty());
+    }
+
+    #[test]
+    fn test_workspace_manager_current_workspace_fuzz() {
+        let names: Vec<String> = (0..8).map(|i| format!("workspace{i}")).collect();
+        let workspace = |name: &String| Workspace::with_path(name.clone(), format!("~/{name}"));
+
+        let mut workspace_manager =
+            WorkspaceManager::from_single_workspace(workspace(&names[0])).unwrap();
+
+        // A small linear congruential generator keeps the sequence reproducible.
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as usize
+        };
+
+        for _ in 0..10_000 {
+            let name = &names[next() % names.len()];
+
+            match next() % 3 {
+                0 => workspace_manager.add_workspace(workspace(name)).unwrap(),
+                1 => {
+                    let _ = workspace_manager.remove_workspace(name);
+                }
+                _ => {
+                    let _ = workspace_manager.set_current_workspace(name);
+                }
+            }
+
+            assert!(workspace_manager.get_current_workspace().is_some());
+        }
+    }
 }
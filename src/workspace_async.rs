@@ -0,0 +1,115 @@
+use crate::workspace::Workspace;
+use std::{
+    future::Future,
+    io,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+struct Shared<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A future resolving to the result of a blocking operation running on a background thread.
+///
+/// Dropping the future does not interrupt the operation; its result is simply discarded
+/// once the thread finishes, so nothing is ever left half-done.
+pub struct BackgroundTask<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    fn spawn(task: impl FnOnce() -> T + Send + 'static) -> BackgroundTask<T> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let thread_shared = Arc::clone(&shared);
+
+        thread::spawn(move || {
+            let result = task();
+            let mut shared = thread_shared.lock().unwrap_or_else(|err| err.into_inner());
+
+            shared.result = Some(result);
+
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+
+        BackgroundTask { shared }
+    }
+}
+
+impl<T> Future for BackgroundTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(|err| err.into_inner());
+
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Workspace {
+    /// Asynchronously returns all `.norg` files in the workspace, relative to the workspace root.
+    /// Follows the same filtering rules as [`Workspace::files`].
+    ///
+    /// The walk runs on a background thread so it never blocks the executor,
+    /// and the returned future works with any async runtime.
+    pub fn files_async(&self) -> BackgroundTask<io::Result<Vec<PathBuf>>> {
+        let workspace = self.clone();
+
+        BackgroundTask::spawn(move || workspace.files())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_files_async() {
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
+
+        let mut files = block_on(workspace.files_async()).expect("Unable to list files");
+        let mut expected = workspace.files().expect("Unable to list files");
+        files.sort();
+        expected.sort();
+
+        assert_eq!(files, expected);
+    }
+}
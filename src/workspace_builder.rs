@@ -0,0 +1,184 @@
+use crate::expand::expand_path;
+use crate::workspace::{Workspace, WorkspaceError, WorkspaceManager};
+use std::path::PathBuf;
+
+/// Incrementally builds a [`WorkspaceManager`].
+#[derive(Debug, Default)]
+pub struct WorkspaceManagerBuilder {
+    workspaces: Vec<Workspace>,
+    default_workspace: Option<String>,
+    /// The first error expanding a workspace path, reported by [`WorkspaceManagerBuilder::build`].
+    error: Option<WorkspaceError>,
+}
+
+impl WorkspaceManagerBuilder {
+    /// Creates a new builder without any workspaces.
+    pub fn new() -> WorkspaceManagerBuilder {
+        <WorkspaceManagerBuilder as Default>::default()
+    }
+
+    /// Adds a workspace to the builder.
+    /// Overwrites any previously added workspace with the same name.
+    /// The path is expanded like the paths of a configuration file, e.g. `~/notes`.
+    /// The name and the path are validated when the workspace manager is built.
+    ///
+    /// * `name`: The name of the workspace.
+    /// * `path`: The path to the root of the workspace.
+    pub fn workspace(mut self, name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        let mut path = path.into();
+        if let Some(raw) = path.to_str() {
+            match expand_path(raw) {
+                Ok(expanded) => path = expanded,
+                Err(err) => {
+                    self.error.get_or_insert(err.into());
+                }
+            }
+        }
+        let workspace = Workspace::with_path(name.into().trim(), path);
+
+        match self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.name == workspace.name)
+        {
+            Some(existing) => *existing = workspace,
+            None => self.workspaces.push(workspace),
+        }
+
+        self
+    }
+
+    /// Sets the name of the default workspace.
+    /// If this is never called, the first added workspace becomes the default.
+    ///
+    /// * `name`: The name of the default workspace.
+    pub fn default(mut self, name: impl Into<String>) -> Self {
+        self.default_workspace = Some(name.into().trim().to_string());
+        self
+    }
+
+    /// Builds the workspace manager.
+    /// If a workspace name is invalid (see [`Workspace::validate_name`]), a workspace path
+    /// could not be expanded or the default workspace was never added (or no workspaces
+    /// were added at all), an error is returned.
+    pub fn build(self) -> Result<WorkspaceManager, WorkspaceError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let default_workspace = self
+            .default_workspace
+            .or_else(|| self.workspaces.first().map(|w| w.name.clone()))
+            .unwrap_or_default();
+
+        WorkspaceManager::new(self.workspaces, &default_workspace)
+    }
+}
+
+impl WorkspaceManager {
+    /// Returns a builder to incrementally construct a workspace manager.
+    pub fn builder() -> WorkspaceManagerBuilder {
+        WorkspaceManagerBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expand::ExpandError;
+    use crate::workspace::{NameError, WorkspaceNotFound};
+
+    #[test]
+    fn test_builder_default_workspace() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "~/notes")
+            .workspace("work", "~/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "notes"
+        );
+
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "~/notes")
+            .workspace("work", "~/work")
+            .default("work")
+            .build()
+            .expect("Unable to build the workspace manager");
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().name,
+            "work"
+        );
+    }
+
+    #[test]
+    fn test_builder_overwrite_and_validation() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "~/notes")
+            .workspace("notes", "~/other-notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+        assert_eq!(workspace_manager.names().count(), 1);
+        assert_eq!(
+            workspace_manager.get_current_workspace().unwrap().path,
+            expand_path("~/other-notes").unwrap()
+        );
+
+        let err = WorkspaceManager::builder()
+            .workspace("notes", "~/notes")
+            .default("missing")
+            .build()
+            .expect_err("A missing default workspace must be rejected");
+        assert_eq!(
+            err,
+            WorkspaceError::NotFound(WorkspaceNotFound {
+                workspace: "missing".to_string()
+            })
+        );
+
+        assert!(WorkspaceManager::builder().build().is_err());
+
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace(" notes ", "~/notes")
+            .default("notes")
+            .build()
+            .expect("Surrounding whitespace must be trimmed");
+        assert_eq!(workspace_manager.names_sorted(), vec!["notes"]);
+
+        let err = WorkspaceManager::builder()
+            .workspace("notes/work", "~/notes")
+            .build()
+            .expect_err("An invalid name must be rejected");
+        assert_eq!(
+            err,
+            WorkspaceError::InvalidName(NameError::Separator("notes/work".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_builder_expands_paths() {
+        std::env::set_var("NEORG_DIRMAN_TEST_BUILDER_VAR", "/data");
+        std::env::remove_var("NEORG_DIRMAN_TEST_BUILDER_UNSET");
+
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "$NEORG_DIRMAN_TEST_BUILDER_VAR/notes")
+            .build()
+            .expect("Unable to build the workspace manager");
+        assert_eq!(
+            workspace_manager.get_workspace("notes").unwrap().path,
+            PathBuf::from("/data/notes")
+        );
+
+        let err = WorkspaceManager::builder()
+            .workspace("notes", "$NEORG_DIRMAN_TEST_BUILDER_UNSET/notes")
+            .build()
+            .expect_err("An unexpandable path must be rejected");
+        assert_eq!(
+            err,
+            WorkspaceError::InvalidPath(ExpandError::VarNotFound(
+                "NEORG_DIRMAN_TEST_BUILDER_UNSET".to_string()
+            ))
+        );
+    }
+}
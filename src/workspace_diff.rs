@@ -0,0 +1,114 @@
+use crate::workspace::{Workspace, WorkspaceManager};
+use std::path::PathBuf;
+
+/// A workspace whose path differs between two workspace managers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedWorkspace {
+    /// The name of the workspace.
+    pub name: String,
+    /// The path of the workspace in the old workspace manager.
+    pub old_path: PathBuf,
+    /// The path of the workspace in the new workspace manager.
+    pub new_path: PathBuf,
+}
+
+/// The differences between two workspace managers, as returned by
+/// [`WorkspaceManager::diff`]. All lists are sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManagerDiff {
+    /// The names of the workspaces only present in the new workspace manager.
+    pub added: Vec<String>,
+    /// The names of the workspaces only present in the old workspace manager.
+    pub removed: Vec<String>,
+    /// The workspaces present in both workspace managers, but with different paths.
+    pub changed: Vec<ChangedWorkspace>,
+}
+
+impl ManagerDiff {
+    /// Returns true if both workspace managers have the same workspaces at the same paths.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Returns true if both paths point at the same directory, comparing them after expansion
+/// and canonicalization if they are spelled differently.
+fn same_path(old: &Workspace, new: &Workspace) -> bool {
+    old.path == new.path
+        || old
+            .location()
+            .is_some_and(|location| Some(location) == new.location())
+}
+
+impl WorkspaceManager {
+    /// Compares this workspace manager with an older one, e.g. after reloading the
+    /// configuration, returning which workspaces were added, removed or moved.
+    /// Paths spelled differently but pointing at the same directory, like `~/notes` and
+    /// `/home/user/notes`, are not considered a change. The current, default and fallback
+    /// workspaces are not compared.
+    ///
+    /// * `old`: The workspace manager to compare with.
+    pub fn diff(&self, old: &WorkspaceManager) -> ManagerDiff {
+        let mut diff = ManagerDiff::default();
+
+        for new in self.workspaces() {
+            match old.workspaces.get(&new.name) {
+                None => diff.added.push(new.name.clone()),
+                Some(old) if !same_path(old, new) => diff.changed.push(ChangedWorkspace {
+                    name: new.name.clone(),
+                    old_path: old.path.clone(),
+                    new_path: new.path.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = old
+            .names()
+            .filter(|name| !self.workspaces.contains_key(*name))
+            .map(str::to_string)
+            .collect();
+
+        diff.added.sort_unstable();
+        diff.removed.sort_unstable();
+        diff.changed.sort_by(|a, b| a.name.cmp(&b.name));
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let old = WorkspaceManager::without_current_workspace(vec![
+            Workspace::with_path("notes", dir.path().join("notes")),
+            Workspace::with_path("work", dir.path().join("work")),
+            Workspace::with_path("archive", dir.path().join("archive")),
+        ])
+        .unwrap();
+        let new = WorkspaceManager::without_current_workspace(vec![
+            Workspace::with_path("notes", dir.path().join("other/../notes")),
+            Workspace::with_path("work", dir.path().join("work2")),
+            Workspace::with_path("journal", dir.path().join("journal")),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            new.diff(&old),
+            ManagerDiff {
+                added: vec!["journal".to_string()],
+                removed: vec!["archive".to_string()],
+                changed: vec![ChangedWorkspace {
+                    name: "work".to_string(),
+                    old_path: dir.path().join("work"),
+                    new_path: dir.path().join("work2"),
+                }],
+            }
+        );
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+}
@@ -0,0 +1,1547 @@
+use crate::filesystem::{FileSystem, StdFileSystem};
+use crate::journal::Date;
+use crate::workspace::{Workspace, WorkspaceError, WorkspaceManager, WorkspaceNotFound};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the file acting as the entry point of a workspace or category.
+pub const INDEX_FILE_NAME: &str = "index.norg";
+
+/// The folder inside a workspace that deleted notes are moved to with [`DeleteMode::Trash`].
+pub const TRASH_FOLDER: &str = ".trash";
+
+/// The folder inside a workspace that [`Workspace::create_scratch`] creates notes in.
+pub const SCRATCH_FOLDER: &str = "scratch";
+
+/// How [`Workspace::delete_file`] deletes a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteMode {
+    /// The note is removed from the filesystem.
+    Permanent,
+    /// The note is moved into the workspace's [`TRASH_FOLDER`], keeping its relative path
+    /// and with a timestamp appended to its name, so it can be restored later.
+    Trash,
+}
+
+/// How [`Workspace::write_file`] treats an existing note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Fail with an `AlreadyExists` error if the note exists.
+    CreateNew,
+    /// Replace the contents of the note if it exists.
+    Overwrite,
+    /// Add the contents to the end of the note if it exists.
+    Append,
+}
+
+/// A callback registered with [`WorkspaceManager::on_create`].
+pub type CreateHook = dyn Fn(&Workspace, &Path) + Send + Sync;
+
+/// A file operation planned by [`Workspace::plan_create_file`],
+/// [`Workspace::plan_delete_file`] or [`WorkspaceManager::plan_move_file`], describing
+/// what the operation would do without touching the filesystem. All paths are absolute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAction {
+    /// An empty note would be created.
+    Create(PathBuf),
+    /// A note would be removed from the filesystem.
+    Delete(PathBuf),
+    /// A note would be moved, either into another workspace or into the trash.
+    Move { from: PathBuf, to: PathBuf },
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedAction::Create(path) => write!(f, "create \"{}\"", path.display()),
+            PlannedAction::Delete(path) => write!(f, "delete \"{}\"", path.display()),
+            PlannedAction::Move { from, to } => {
+                write!(f, "move \"{}\" to \"{}\"", from.display(), to.display())
+            }
+        }
+    }
+}
+
+impl PlannedAction {
+    /// Carries out the planned action. Intermediate directories are created as needed,
+    /// and existing files are never overwritten even if they appeared after planning.
+    pub fn execute(&self) -> io::Result<()> {
+        self.execute_on(&StdFileSystem)
+    }
+
+    /// Like [`PlannedAction::execute`], but on the given filesystem rather than the
+    /// real one, e.g. the [`Workspace::filesystem`] of the workspace it was planned for.
+    ///
+    /// * `filesystem`: The filesystem to carry out the action on.
+    pub fn execute_on(&self, filesystem: &dyn FileSystem) -> io::Result<()> {
+        match self {
+            PlannedAction::Create(path) => {
+                if let Some(parent) = path.parent() {
+                    filesystem.create_dir_all(parent)?;
+                }
+
+                filesystem.write(path, &[], true)
+            }
+            PlannedAction::Delete(path) => filesystem.remove_file(path),
+            PlannedAction::Move { from, to } => filesystem.rename(from, to),
+        }
+    }
+}
+
+pub(crate) fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("\"{}\" not found", path.display()),
+    )
+}
+
+pub(crate) fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("\"{}\" already exists", path.display()),
+    )
+}
+
+/// Returns a unique path for a hidden temporary file next to `path`, to be renamed into
+/// place once it has been written completely.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    dir.join(temp_name)
+}
+
+/// Writes a file atomically by writing to a hidden temporary file in the same directory
+/// and renaming it into place, so readers never observe a partially written file.
+/// With `create_new` the file is linked into place instead, failing if it already exists.
+/// On filesystems without hard links the file is then created and written in place.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8], create_new: bool) -> io::Result<()> {
+    let temp = temp_path(path);
+
+    let result = fs::File::create_new(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| {
+            if create_new {
+                fs::hard_link(&temp, path)
+            } else {
+                fs::rename(&temp, path)
+            }
+        });
+
+    if result.is_err() || create_new {
+        let _ = fs::remove_file(&temp);
+    }
+
+    match result {
+        Err(err)
+            if create_new
+                && matches!(
+                    err.kind(),
+                    io::ErrorKind::Unsupported | io::ErrorKind::PermissionDenied
+                ) =>
+        {
+            write_new(path, contents)
+        }
+        result => result,
+    }
+}
+
+/// Creates a file that must not exist yet and writes it, removing the partial file
+/// on failure.
+fn write_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .inspect_err(|_| {
+            let _ = fs::remove_file(path);
+        })
+}
+
+/// Moves a file, falling back to copying and deleting it when the source and
+/// destination are on different filesystems or the filesystem cannot rename it.
+/// Fails with an `AlreadyExists` error if the destination already exists.
+pub(crate) fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    // A missing source must not leave the destination directories behind.
+    fs::symlink_metadata(from)?;
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match rename_no_replace(from, to) {
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Err(already_exists(to)),
+        Err(err)
+            if matches!(
+                err.kind(),
+                io::ErrorKind::CrossesDevices
+                    | io::ErrorKind::Unsupported
+                    | io::ErrorKind::PermissionDenied
+            ) =>
+        {
+            copy_new(from, to)?;
+            fs::remove_file(from).inspect_err(|_| {
+                let _ = fs::remove_file(to);
+            })
+        }
+        result => result,
+    }
+}
+
+/// Renames a file unless the destination exists. On Linux this is a single atomic
+/// `renameat2` call; elsewhere, and on filesystems not supporting it, the destination
+/// is checked before renaming, so a file created in between would be replaced.
+fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let c_path = |path: &Path| {
+            CString::new(path.as_os_str().as_bytes())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+        };
+        let (c_from, c_to) = (c_path(from)?, c_path(to)?);
+
+        // SAFETY: Both paths are valid NUL-terminated strings.
+        let result = unsafe {
+            libc::renameat2(
+                libc::AT_FDCWD,
+                c_from.as_ptr(),
+                libc::AT_FDCWD,
+                c_to.as_ptr(),
+                libc::RENAME_NOREPLACE,
+            )
+        };
+        if result == 0 {
+            return Ok(());
+        }
+
+        // Old kernels and some filesystems don't support the flag.
+        let err = io::Error::last_os_error();
+        if !matches!(err.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS)) {
+            return Err(err);
+        }
+    }
+
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(already_exists(to));
+    }
+
+    fs::rename(from, to)
+}
+
+/// Copies a file to a destination that must not exist yet, removing the partial copy
+/// on failure.
+fn copy_new(from: &Path, to: &Path) -> io::Result<()> {
+    let mut source = fs::File::open(from)?;
+    let permissions = source.metadata()?.permissions();
+    let mut destination = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::AlreadyExists => already_exists(to),
+            _ => err,
+        })?;
+
+    io::copy(&mut source, &mut destination)
+        .and_then(|_| destination.set_permissions(permissions))
+        .and_then(|()| destination.sync_all())
+        .inspect_err(|_| {
+            let _ = fs::remove_file(to);
+        })
+}
+
+/// Returns a workspace for every non-hidden subdirectory of `parent`, sorted by name.
+fn workspaces_in(parent: &Path) -> io::Result<Vec<Workspace>> {
+    let mut workspaces = Vec::new();
+
+    for entry in fs::read_dir(parent)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            if let Ok(name) = entry.file_name().into_string() {
+                if !name.starts_with('.') && Workspace::validate_name(&name) == Ok(&name) {
+                    workspaces.push(Workspace::with_path(name, entry.path()));
+                }
+            }
+        }
+    }
+
+    workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(workspaces)
+}
+
+impl Workspace {
+    /// Creates the workspace directory and all of its parents.
+    /// Succeeds without doing anything if the directory already exists.
+    pub fn create_dir_all(&self) -> io::Result<()> {
+        self.filesystem().create_dir_all(&self.path)
+    }
+
+    /// Fails with [`WorkspaceError::ReadOnly`] if the workspace is read-only.
+    pub(crate) fn ensure_writable(&self) -> Result<(), WorkspaceError> {
+        if self.read_only {
+            Err(WorkspaceError::ReadOnly(self.name.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves a note name through [`Workspace::file_path`] into an absolute path,
+    /// returning a [`WorkspaceError::PathEscape`] error if the name escapes the workspace.
+    pub(crate) fn resolve_file_path(&self, rel: &str) -> Result<PathBuf, WorkspaceError> {
+        Ok(std::path::absolute(self.checked_file_path(rel)?)?)
+    }
+
+    /// Returns true if the note exists in the workspace.
+    /// Like [`Workspace::file_path`], appends the workspace's
+    /// [primary extension](Workspace::primary_extension) if the name has none,
+    /// and returns false for names escaping the workspace.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn file_exists(&self, rel: &str) -> bool {
+        self.file_path(rel)
+            .is_some_and(|path| self.filesystem().is_file(&path))
+    }
+
+    /// Returns true if the file exists in the workspace, taking its name as-is rather
+    /// than assuming the primary extension, e.g. for attachments.
+    /// Returns false for names escaping the workspace.
+    ///
+    /// * `rel`: The relative path of the file, e.g. `images/diagram.png`.
+    pub fn file_exists_any_ext(&self, rel: &str) -> bool {
+        self.join_relative(rel)
+            .is_some_and(|path| self.filesystem().is_file(&path))
+    }
+
+    /// Creates a new, empty note in the workspace and returns its absolute path.
+    /// Intermediate directories are created as needed.
+    /// Fails with [`WorkspaceError::AlreadyExists`] rather than truncating an existing note.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn create_file(&self, rel: &str) -> Result<PathBuf, WorkspaceError> {
+        self.ensure_writable()?;
+        let path = self.resolve_file_path(rel)?;
+
+        PlannedAction::Create(path.clone())
+            .execute_on(self.filesystem())
+            .map_err(|err| note_error(err, &path))?;
+
+        Ok(path)
+    }
+
+    /// Plans [`Workspace::create_file`] without touching the filesystem, failing with the
+    /// same errors that creating the note would.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn plan_create_file(&self, rel: &str) -> Result<PlannedAction, WorkspaceError> {
+        self.ensure_writable()?;
+        let path = self.resolve_file_path(rel)?;
+
+        if self.filesystem().exists(&path) {
+            return Err(WorkspaceError::AlreadyExists(path.display().to_string()));
+        }
+
+        Ok(PlannedAction::Create(path))
+    }
+
+    /// Creates a new, empty scratch note named after the current UTC time, e.g.
+    /// `scratch/2024-06-01T12-30-00.norg`, and returns its absolute path.
+    /// Notes created within the same second get a counter appended, e.g.
+    /// `scratch/2024-06-01T12-30-00-1.norg`.
+    pub fn create_scratch(&self) -> Result<PathBuf, WorkspaceError> {
+        self.create_scratch_at(SystemTime::now())
+    }
+
+    fn create_scratch_at(&self, now: SystemTime) -> Result<PathBuf, WorkspaceError> {
+        let seconds = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let date = Date::from_days_since_epoch((seconds / 86_400) as i64);
+        let time = seconds % 86_400;
+        let name = format!(
+            "{SCRATCH_FOLDER}/{date}T{:02}-{:02}-{:02}",
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        );
+
+        for counter in 0.. {
+            let rel = match counter {
+                0 => name.clone(),
+                counter => format!("{name}-{counter}"),
+            };
+
+            match self.create_file(&rel) {
+                Err(WorkspaceError::AlreadyExists(_)) => continue,
+                result => return result,
+            }
+        }
+
+        unreachable!("the counter is unbounded")
+    }
+
+    /// Sets the modification time of an existing note to now, e.g. to mark it as
+    /// recently opened. Fails with a `NotFound` error if the note doesn't exist.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn touch(&self, rel: &str) -> Result<(), WorkspaceError> {
+        self.touch_with(rel, false)
+    }
+
+    /// Like [`Workspace::touch`], but creates an empty note (and its parent directories)
+    /// if it doesn't exist yet.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn touch_or_create(&self, rel: &str) -> Result<(), WorkspaceError> {
+        self.touch_with(rel, true)
+    }
+
+    fn touch_with(&self, rel: &str, create: bool) -> Result<(), WorkspaceError> {
+        self.ensure_writable()?;
+        let path = self.resolve_file_path(rel)?;
+
+        if create {
+            if let Some(parent) = path.parent() {
+                self.filesystem().create_dir_all(parent)?;
+            }
+        }
+
+        Ok(self.filesystem().touch(&path, create)?)
+    }
+
+    /// Deletes a note from the workspace.
+    /// Fails with a `NotFound` I/O error if the note doesn't exist and with
+    /// [`WorkspaceError::PathEscape`] if it lies outside of the workspace.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `mode`: Whether to remove the note or move it into the trash.
+    pub fn delete_file(&self, rel: &str, mode: DeleteMode) -> Result<(), WorkspaceError> {
+        Ok(self
+            .plan_delete_file(rel, mode)?
+            .execute_on(self.filesystem())?)
+    }
+
+    /// Plans [`Workspace::delete_file`] without touching the filesystem, failing with the
+    /// same errors that deleting the note would. With [`DeleteMode::Trash`] the plan is
+    /// a [`PlannedAction::Move`] into the trash.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `mode`: Whether to remove the note or move it into the trash.
+    pub fn plan_delete_file(
+        &self,
+        rel: &str,
+        mode: DeleteMode,
+    ) -> Result<PlannedAction, WorkspaceError> {
+        self.ensure_writable()?;
+        let path = self.resolve_file_path(rel)?;
+
+        if !self.filesystem().exists(&path) {
+            return Err(not_found(&path).into());
+        }
+        if !self.filesystem().is_file(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("\"{}\" is not a file", path.display()),
+            )
+            .into());
+        }
+
+        match mode {
+            DeleteMode::Permanent => Ok(PlannedAction::Delete(path)),
+            DeleteMode::Trash => {
+                let root = std::path::absolute(&self.path)?;
+                let relative = path.strip_prefix(&root).unwrap_or(&path);
+                let millis = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis());
+
+                let mut file_name = relative.file_stem().unwrap_or_default().to_os_string();
+                file_name.push(format!("-{millis}"));
+                if let Some(extension) = relative.extension() {
+                    file_name.push(".");
+                    file_name.push(extension);
+                }
+
+                let trashed = root
+                    .join(TRASH_FOLDER)
+                    .join(relative)
+                    .with_file_name(file_name);
+
+                Ok(PlannedAction::Move {
+                    from: path,
+                    to: trashed,
+                })
+            }
+        }
+    }
+
+    /// Renames or moves a note within the workspace and returns its new absolute path.
+    /// Missing destination directories are created.
+    ///
+    /// Fails with a `NotFound` I/O error if the note doesn't exist, with
+    /// [`WorkspaceError::PathEscape`] if either name escapes the workspace and with
+    /// [`WorkspaceError::AlreadyExists`] if the destination note already exists.
+    ///
+    /// * `from_rel`: The current relative name of the note, e.g. `projects/todo`.
+    /// * `to_rel`: The new relative name of the note, e.g. `archive/todo`.
+    pub fn rename_file(&self, from_rel: &str, to_rel: &str) -> Result<PathBuf, WorkspaceError> {
+        self.ensure_writable()?;
+        let from = self.resolve_file_path(from_rel)?;
+        let to = self.resolve_file_path(to_rel)?;
+
+        if !self.filesystem().exists(&from) {
+            return Err(not_found(&from).into());
+        }
+        self.filesystem()
+            .rename(&from, &to)
+            .map_err(|err| note_error(err, &to))?;
+
+        Ok(to)
+    }
+
+    /// Writes a note atomically and returns its absolute path, so a crash never leaves
+    /// a half-written note behind. Intermediate directories are created as needed.
+    /// With [`WriteMode::CreateNew`] it fails with [`WorkspaceError::AlreadyExists`]
+    /// if the note exists.
+    ///
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `contents`: The contents to write.
+    /// * `mode`: How to treat an existing note.
+    pub fn write_file(
+        &self,
+        rel: &str,
+        contents: &str,
+        mode: WriteMode,
+    ) -> Result<PathBuf, WorkspaceError> {
+        self.ensure_writable()?;
+        let path = self.resolve_file_path(rel)?;
+
+        let filesystem = self.filesystem();
+        if let Some(parent) = path.parent() {
+            filesystem.create_dir_all(parent)?;
+        }
+
+        match mode {
+            WriteMode::CreateNew => filesystem.write(&path, contents.as_bytes(), true),
+            WriteMode::Overwrite => filesystem.write(&path, contents.as_bytes(), false),
+            WriteMode::Append => match filesystem.read(&path) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+                result => result,
+            }
+            .and_then(|mut existing| {
+                existing.extend_from_slice(contents.as_bytes());
+                filesystem.write(&path, &existing, false)
+            }),
+        }
+        .map_err(|err| note_error(err, &path))?;
+
+        Ok(path)
+    }
+
+    /// Returns the path of the workspace's `index.norg` entry point.
+    pub fn index_path(&self) -> PathBuf {
+        self.index_path_named(INDEX_FILE_NAME)
+    }
+
+    /// Returns the path of the workspace's entry point with a custom file name,
+    /// for users whose index is e.g. `main.norg`.
+    ///
+    /// * `file_name`: The file name of the index, including its extension.
+    pub fn index_path_named(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+
+    /// Returns true if the workspace has an `index.norg` entry point.
+    pub fn has_index(&self) -> bool {
+        self.filesystem().is_file(&self.index_path())
+    }
+
+    /// Returns the path of the `index.norg` of the category containing a note, or of the
+    /// nearest ancestor category that has one, e.g. for "up to parent" navigation.
+    /// For an index itself the search starts at the category above it.
+    ///
+    /// Returns None if neither the category nor any ancestor up to the workspace root
+    /// has an index, or if the path is not a relative path inside the workspace.
+    ///
+    /// * `rel`: The path of the note relative to the workspace root, e.g. `projects/todo.norg`.
+    pub fn parent_index(&self, rel: &Path) -> Option<PathBuf> {
+        if !rel
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return None;
+        }
+
+        let category = rel.parent()?;
+        let start = if rel.file_name()? == INDEX_FILE_NAME {
+            category.parent()?
+        } else {
+            category
+        };
+
+        start
+            .ancestors()
+            .map(|dir| self.path.join(dir).join(INDEX_FILE_NAME))
+            .find(|index| self.filesystem().is_file(index))
+    }
+
+    /// Creates an empty `index.norg` if the workspace doesn't have one yet
+    /// and returns its path. An existing index is left untouched, so this only fails
+    /// for read-only workspaces if the index is missing.
+    pub fn ensure_index(&self) -> Result<PathBuf, WorkspaceError> {
+        self.ensure_index_named(INDEX_FILE_NAME)
+    }
+
+    /// Like [`Workspace::ensure_index`], but with a custom index file name.
+    ///
+    /// * `file_name`: The file name of the index, including its extension.
+    pub fn ensure_index_named(&self, file_name: &str) -> Result<PathBuf, WorkspaceError> {
+        let path = self.index_path_named(file_name);
+
+        if self.filesystem().is_file(&path) {
+            return Ok(path);
+        }
+        self.ensure_writable()?;
+        self.create_dir_all()?;
+
+        match self.filesystem().write(&path, &[], true) {
+            Err(err) if err.kind() != io::ErrorKind::AlreadyExists => Err(err.into()),
+            _ => Ok(path),
+        }
+    }
+}
+
+impl WorkspaceManager {
+    /// Like [`WorkspaceManager::from_single_workspace`], but first creates the workspace
+    /// directory and all of its parents. An existing directory is left untouched.
+    ///
+    /// * `workspace`: The single workspace to use.
+    pub fn from_single_workspace_creating(workspace: Workspace) -> io::Result<WorkspaceManager> {
+        let workspace_manager = WorkspaceManager::from_single_workspace(workspace)?;
+        workspace_manager
+            .get_current_workspace()
+            .expect("the single workspace is the current workspace")
+            .create_dir_all()?;
+
+        Ok(workspace_manager)
+    }
+
+    /// Creates a workspace manager with a workspace for every immediate subdirectory of
+    /// `parent`, named after the directory, e.g. `~/notes/work` becomes the workspace `work`.
+    /// Hidden directories and directory names that are not valid workspace names are skipped.
+    ///
+    /// The alphabetically first workspace becomes the default and current workspace;
+    /// if there are no subdirectories there is none.
+    /// See [`WorkspaceManager::from_parent_dir_with_default`] to choose the default.
+    ///
+    /// * `parent`: The directory containing the workspaces.
+    pub fn from_parent_dir(parent: &Path) -> io::Result<WorkspaceManager> {
+        let workspaces = workspaces_in(parent)?;
+
+        Ok(match workspaces.first().map(|w| w.name.clone()) {
+            Some(default) => WorkspaceManager::new(workspaces, &default)?,
+            None => WorkspaceManager::without_current_workspace(workspaces)?,
+        })
+    }
+
+    /// Like [`WorkspaceManager::from_parent_dir`], but with an explicitly named default
+    /// workspace. Fails with a `NotFound` error if `parent` has no such subdirectory.
+    ///
+    /// * `parent`: The directory containing the workspaces.
+    /// * `default_workspace`: The name of the default workspace.
+    pub fn from_parent_dir_with_default(
+        parent: &Path,
+        default_workspace: &str,
+    ) -> io::Result<WorkspaceManager> {
+        Ok(WorkspaceManager::new(
+            workspaces_in(parent)?,
+            default_workspace,
+        )?)
+    }
+
+    /// Creates the workspace directory if needed and adds the workspace to the list of
+    /// workspaces, overwriting any existing workspace with the same name.
+    /// The workspace is not added if its name is not valid or its directory cannot be
+    /// created, and the directory is not created for an invalid name.
+    ///
+    /// * `workspace`: The workspace to create and add.
+    pub fn add_and_create(&mut self, workspace: Workspace) -> io::Result<()> {
+        Workspace::validate_name(&workspace.name).map_err(WorkspaceError::from)?;
+        workspace.create_dir_all()?;
+
+        Ok(self.add_workspace(workspace)?)
+    }
+
+    /// Registers a callback invoked after a note was created through
+    /// [`WorkspaceManager::create_file`] or [`WorkspaceManager::write_file`], e.g. to log
+    /// the event. Callbacks are invoked in registration order with the workspace and the
+    /// absolute path of the new note.
+    ///
+    /// Callbacks must be thread-safe, so that the workspace manager can still be shared
+    /// between threads.
+    ///
+    /// * `f`: The callback to invoke.
+    pub fn on_create(&mut self, f: Box<CreateHook>) {
+        self.create_hooks.0.push(Arc::from(f));
+    }
+
+    fn run_create_hooks(&self, workspace: &Workspace, path: &Path) {
+        for hook in &self.create_hooks.0 {
+            hook(workspace, path);
+        }
+    }
+
+    /// Creates a new, empty note in a workspace like [`Workspace::create_file`] and invokes
+    /// the callbacks registered with [`WorkspaceManager::on_create`].
+    ///
+    /// * `ws`: The name of the workspace.
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    pub fn create_file(&self, ws: &str, rel: &str) -> Result<PathBuf, WorkspaceError> {
+        let workspace = self.writable_workspace(ws)?;
+        let path = workspace.create_file(rel)?;
+
+        self.run_create_hooks(workspace, &path);
+
+        Ok(path)
+    }
+
+    /// Writes a note in a workspace like [`Workspace::write_file`]. If the note didn't
+    /// exist before, the callbacks registered with [`WorkspaceManager::on_create`] are
+    /// invoked.
+    ///
+    /// * `ws`: The name of the workspace.
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `contents`: The contents to write.
+    /// * `mode`: How to treat an existing note.
+    pub fn write_file(
+        &self,
+        ws: &str,
+        rel: &str,
+        contents: &str,
+        mode: WriteMode,
+    ) -> Result<PathBuf, WorkspaceError> {
+        let workspace = self.writable_workspace(ws)?;
+        let path = workspace.resolve_file_path(rel)?;
+        let existed = workspace.filesystem().is_file(&path);
+        workspace.write_file(rel, contents, mode)?;
+
+        if !existed {
+            self.run_create_hooks(workspace, &path);
+        }
+
+        Ok(path)
+    }
+
+    /// Saves the name of the current workspace to a small state file, so that
+    /// [`WorkspaceManager::load_state`] can restore it when the application restarts.
+    /// Without a current workspace the file is left empty. The file is written atomically.
+    ///
+    /// * `path`: The path of the state file.
+    pub fn save_state(&self, path: &Path) -> io::Result<()> {
+        let state = match &self.current_workspace {
+            Some(name) => format!("{name}\n"),
+            None => String::new(),
+        };
+
+        write_atomically(path, state.as_bytes(), false)
+    }
+
+    /// Restores the current workspace saved with [`WorkspaceManager::save_state`].
+    /// If the state file doesn't exist, is empty or names a workspace that no longer
+    /// exists, the default workspace becomes the current workspace instead.
+    /// Only errors reading an existing state file are returned.
+    ///
+    /// * `path`: The path of the state file.
+    pub fn load_state(&mut self, path: &Path) -> io::Result<()> {
+        let state = match fs::read_to_string(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            result => result?,
+        };
+
+        if self.set_current_workspace(state.trim()).is_err() {
+            self.reset_to_default();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the workspace with the given name, failing if it doesn't exist or is read-only.
+    fn writable_workspace(&self, name: &str) -> Result<&Workspace, WorkspaceError> {
+        let workspace = self.get_workspace(name).ok_or_else(|| WorkspaceNotFound {
+            workspace: name.to_string(),
+        })?;
+
+        if workspace.read_only {
+            return Err(WorkspaceError::ReadOnly(workspace.name.clone()));
+        }
+
+        Ok(workspace)
+    }
+
+    /// Moves a note from one workspace to another, keeping its relative path,
+    /// and returns its new absolute path. Missing destination directories are created.
+    ///
+    /// Fails with [`WorkspaceError::NotFound`] if either workspace doesn't exist, with
+    /// [`WorkspaceError::ReadOnly`] if either workspace is read-only and with
+    /// [`WorkspaceError::AlreadyExists`] if the destination note already exists.
+    /// The note is renamed if both workspaces share a [`Workspace::filesystem`], and
+    /// otherwise copied onto the filesystem of the destination workspace and then
+    /// removed from the source one.
+    ///
+    /// * `src_ws`: The name of the workspace containing the note.
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `dst_ws`: The name of the workspace to move the note to.
+    pub fn move_file(
+        &self,
+        src_ws: &str,
+        rel: &str,
+        dst_ws: &str,
+    ) -> Result<PathBuf, WorkspaceError> {
+        let (src, dst, from, to) = self.resolve_move(src_ws, rel, dst_ws)?;
+
+        if src.shares_filesystem(dst) {
+            src.filesystem()
+                .rename(&from, &to)
+                .map_err(|err| note_error(err, &to))?;
+
+            return Ok(to);
+        }
+
+        let contents = src.filesystem().read(&from)?;
+        if let Some(parent) = to.parent() {
+            dst.filesystem().create_dir_all(parent)?;
+        }
+        dst.filesystem()
+            .write(&to, &contents, true)
+            .map_err(|err| note_error(err, &to))?;
+        if let Err(err) = src.filesystem().remove_file(&from) {
+            let _ = dst.filesystem().remove_file(&to);
+            return Err(err.into());
+        }
+
+        Ok(to)
+    }
+
+    /// Plans [`WorkspaceManager::move_file`] without touching the filesystem, failing with
+    /// the same errors that moving the note would.
+    /// A planned action runs on a single filesystem, so notes moving between workspaces
+    /// on different filesystems must be moved with [`WorkspaceManager::move_file`].
+    ///
+    /// * `src_ws`: The name of the workspace containing the note.
+    /// * `rel`: The relative name of the note, e.g. `projects/todo`.
+    /// * `dst_ws`: The name of the workspace to move the note to.
+    pub fn plan_move_file(
+        &self,
+        src_ws: &str,
+        rel: &str,
+        dst_ws: &str,
+    ) -> Result<PlannedAction, WorkspaceError> {
+        let (src, dst, from, to) = self.resolve_move(src_ws, rel, dst_ws)?;
+
+        if !src.filesystem().exists(&from) {
+            return Err(not_found(&from).into());
+        }
+        if dst.filesystem().exists(&to) {
+            return Err(WorkspaceError::AlreadyExists(to.display().to_string()));
+        }
+
+        Ok(PlannedAction::Move { from, to })
+    }
+
+    fn resolve_move(
+        &self,
+        src_ws: &str,
+        rel: &str,
+        dst_ws: &str,
+    ) -> Result<(&Workspace, &Workspace, PathBuf, PathBuf), WorkspaceError> {
+        let src = self.writable_workspace(src_ws)?;
+        let dst = self.writable_workspace(dst_ws)?;
+
+        Ok((
+            src,
+            dst,
+            src.resolve_file_path(rel)?,
+            dst.resolve_file_path(rel)?,
+        ))
+    }
+}
+
+/// Converts an error writing the note at `path` into a [`WorkspaceError`], reporting
+/// existing notes as [`WorkspaceError::AlreadyExists`].
+fn note_error(err: io::Error, path: &Path) -> WorkspaceError {
+    if err.kind() == io::ErrorKind::AlreadyExists {
+        WorkspaceError::AlreadyExists(path.display().to_string())
+    } else {
+        WorkspaceError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::MemoryFileSystem;
+
+    #[test]
+    fn test_add_and_create() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let mut workspace_manager =
+            WorkspaceManager::without_current_workspace(Vec::new()).unwrap();
+        let workspace = Workspace::with_path("example workspace", dir.path().join("a/b/notes"));
+
+        workspace_manager
+            .add_and_create(workspace.clone())
+            .expect("Unable to create the workspace");
+        assert!(workspace.path.is_dir());
+        assert_eq!(workspace_manager.len(), 1);
+
+        workspace
+            .create_dir_all()
+            .expect("An existing directory must not be an error");
+
+        let file = Workspace::with_path("file", PathBuf::from("test/example_workspace/file1.norg"));
+        assert!(workspace_manager.add_and_create(file).is_err());
+        assert_eq!(workspace_manager.len(), 1);
+    }
+
+    #[test]
+    fn test_from_parent_dir() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        for name in ["work", "notes", ".trash"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        std::fs::write(dir.path().join("file.norg"), "").unwrap();
+
+        let workspace_manager =
+            WorkspaceManager::from_parent_dir(dir.path()).expect("Unable to scan the directory");
+        assert_eq!(workspace_manager.names_sorted(), vec!["notes", "work"]);
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+        assert_eq!(
+            workspace_manager.get_workspace("work").unwrap().path,
+            dir.path().join("work")
+        );
+
+        let workspace_manager = WorkspaceManager::from_parent_dir_with_default(dir.path(), "work")
+            .expect("Unable to scan the directory");
+        assert_eq!(workspace_manager.current_workspace_name(), Some("work"));
+        assert_eq!(
+            WorkspaceManager::from_parent_dir_with_default(dir.path(), "missing")
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let empty = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace_manager =
+            WorkspaceManager::from_parent_dir(empty.path()).expect("Unable to scan the directory");
+        assert!(workspace_manager.is_empty());
+        assert!(workspace_manager.get_current_workspace().is_none());
+    }
+
+    #[test]
+    fn test_from_single_workspace_creating() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("notes", dir.path().join("a/notes"));
+
+        let workspace_manager = WorkspaceManager::from_single_workspace_creating(workspace.clone())
+            .expect("Unable to create the workspace");
+        assert!(workspace.path.is_dir());
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+
+        WorkspaceManager::from_single_workspace_creating(workspace)
+            .expect("An existing directory must not be an error");
+    }
+
+    #[test]
+    fn test_create_file() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        let path = workspace
+            .create_file("projects/todo")
+            .expect("Unable to create the note");
+        assert!(path.is_absolute());
+        assert_eq!(path, dir.path().join("projects/todo.norg"));
+        assert!(path.is_file());
+
+        fs::write(&path, "* Todo").expect("Unable to write to the note");
+        let err = workspace
+            .create_file("projects/todo.norg")
+            .expect_err("An existing note must not be recreated");
+        assert!(matches!(err, WorkspaceError::AlreadyExists(_)));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "* Todo");
+
+        let err = workspace
+            .create_file("../escape")
+            .expect_err("A note outside of the workspace must be rejected");
+        assert!(matches!(err, WorkspaceError::PathEscape(_)));
+    }
+
+    #[test]
+    fn test_create_scratch() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("notes", dir.path());
+        let root = std::path::absolute(dir.path()).unwrap();
+        // 2024-06-01T12:30:00Z.
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(1_717_245_000);
+
+        assert_eq!(
+            workspace.create_scratch_at(now).unwrap(),
+            root.join("scratch/2024-06-01T12-30-00.norg")
+        );
+        assert_eq!(
+            workspace.create_scratch_at(now).unwrap(),
+            root.join("scratch/2024-06-01T12-30-00-1.norg")
+        );
+        assert!(workspace.create_scratch().unwrap().is_file());
+    }
+
+    #[test]
+    fn test_file_exists() {
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
+
+        assert!(workspace.file_exists("file1"));
+        assert!(workspace.file_exists("file1.norg"));
+        assert!(!workspace.file_exists("missing"));
+        assert!(!workspace.file_exists("../example_workspace/file1"));
+
+        assert!(workspace.file_exists_any_ext("file1.norg"));
+        assert!(!workspace.file_exists_any_ext("file1"));
+        assert!(!workspace.file_exists_any_ext(""));
+    }
+
+    #[test]
+    fn test_write_file() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        let path = workspace
+            .write_file("projects/todo", "* Todo\n", WriteMode::CreateNew)
+            .expect("Unable to write the note");
+        assert_eq!(path, dir.path().join("projects/todo.norg"));
+
+        let err = workspace
+            .write_file("projects/todo", "* Other\n", WriteMode::CreateNew)
+            .expect_err("An existing note must not be replaced");
+        assert!(matches!(err, WorkspaceError::AlreadyExists(_)));
+
+        workspace
+            .write_file("projects/todo", "- ( ) Buy milk\n", WriteMode::Append)
+            .expect("Unable to append to the note");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "* Todo\n- ( ) Buy milk\n"
+        );
+
+        workspace
+            .write_file("projects/todo", "* Done\n", WriteMode::Overwrite)
+            .expect("Unable to overwrite the note");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "* Done\n");
+
+        // No temporary files are left behind.
+        assert_eq!(
+            fs::read_dir(dir.path().join("projects")).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_touch() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        let err = workspace
+            .touch("todo")
+            .expect_err("A missing note must not be created");
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+
+        workspace
+            .touch_or_create("projects/todo")
+            .expect("Unable to create the note");
+        let path = dir.path().join("projects/todo.norg");
+        fs::write(&path, "* Todo").unwrap();
+
+        let past = SystemTime::UNIX_EPOCH;
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(past))
+            .unwrap();
+
+        workspace
+            .touch("projects/todo")
+            .expect("Unable to touch the note");
+        assert!(fs::metadata(&path).unwrap().modified().unwrap() > past);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "* Todo");
+
+        let err = workspace
+            .touch_or_create("../escape")
+            .expect_err("A note outside of the workspace must be rejected");
+        assert!(matches!(err, WorkspaceError::PathEscape(_)));
+    }
+
+    #[test]
+    fn test_delete_file() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        let path = workspace
+            .create_file("projects/todo")
+            .expect("Unable to create the note");
+        workspace
+            .delete_file("projects/todo", DeleteMode::Trash)
+            .expect("Unable to move the note to the trash");
+        assert!(!path.exists());
+
+        let trashed: Vec<_> = fs::read_dir(dir.path().join(".trash/projects"))
+            .expect("The trash must keep the relative path")
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(trashed.len(), 1);
+        assert!(trashed[0].starts_with("todo-") && trashed[0].ends_with(".norg"));
+        assert!(workspace.files().unwrap().is_empty());
+
+        workspace.create_file("todo").unwrap();
+        workspace
+            .delete_file("todo", DeleteMode::Permanent)
+            .expect("Unable to delete the note");
+        assert!(!dir.path().join("todo.norg").exists());
+
+        let err = workspace
+            .delete_file("todo", DeleteMode::Permanent)
+            .expect_err("A missing note must be rejected");
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+        let err = workspace
+            .delete_file("../escape", DeleteMode::Trash)
+            .expect_err("A note outside of the workspace must be rejected");
+        assert!(matches!(err, WorkspaceError::PathEscape(_)));
+    }
+
+    #[test]
+    fn test_plan_file_actions() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", dir.path().join("notes"))
+            .workspace("work", dir.path().join("work"))
+            .build()
+            .expect("Unable to build the workspace manager");
+        let notes = workspace_manager.get_workspace("notes").unwrap();
+        let root = std::path::absolute(&notes.path).unwrap();
+
+        let create = notes
+            .plan_create_file("projects/todo")
+            .expect("Unable to plan the creation");
+        assert_eq!(
+            create,
+            PlannedAction::Create(root.join("projects/todo.norg"))
+        );
+        assert!(!notes.path.exists());
+        create.execute().expect("Unable to create the note");
+        assert!(notes.file_exists("projects/todo"));
+
+        let plan_error = |result: Result<PlannedAction, WorkspaceError>| {
+            io::Error::from(result.unwrap_err()).kind()
+        };
+        assert_eq!(
+            plan_error(notes.plan_create_file("projects/todo")),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            plan_error(notes.plan_create_file("../escape")),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            plan_error(notes.plan_delete_file("missing", DeleteMode::Permanent)),
+            io::ErrorKind::NotFound
+        );
+        assert!(matches!(
+            workspace_manager.plan_move_file("notes", "../../todo", "work"),
+            Err(WorkspaceError::PathEscape(_))
+        ));
+
+        assert_eq!(
+            notes
+                .plan_delete_file("projects/todo", DeleteMode::Permanent)
+                .unwrap(),
+            PlannedAction::Delete(root.join("projects/todo.norg"))
+        );
+        assert!(matches!(
+            notes.plan_delete_file("projects/todo", DeleteMode::Trash).unwrap(),
+            PlannedAction::Move { to, .. } if to.starts_with(root.join(TRASH_FOLDER))
+        ));
+
+        let work = std::path::absolute(dir.path().join("work")).unwrap();
+        let planned = workspace_manager
+            .plan_move_file("notes", "projects/todo", "work")
+            .expect("Unable to plan the move");
+        assert_eq!(
+            planned.to_string(),
+            format!(
+                "move \"{}\" to \"{}\"",
+                root.join("projects/todo.norg").display(),
+                work.join("projects/todo.norg").display()
+            )
+        );
+        assert!(notes.file_exists("projects/todo"));
+    }
+
+    #[test]
+    fn test_read_only() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("archive")).unwrap();
+        std::fs::write(dir.path().join("archive/old.norg"), "").unwrap();
+
+        let workspace_manager = WorkspaceManager::without_current_workspace(vec![
+            Workspace::with_path("notes", dir.path().join("notes")),
+            Workspace::with_path("archive", dir.path().join("archive")).with_read_only(true),
+        ])
+        .unwrap();
+        let archive = workspace_manager.get_workspace("archive").unwrap();
+
+        let read_only = |result: Result<(), WorkspaceError>| matches!(result, Err(WorkspaceError::ReadOnly(name)) if name == "archive");
+        assert!(read_only(archive.create_file("new").map(drop)));
+        assert!(read_only(
+            archive
+                .write_file("old", "", WriteMode::Overwrite)
+                .map(drop)
+        ));
+        assert!(read_only(archive.delete_file("old", DeleteMode::Trash)));
+        assert!(read_only(archive.touch("old")));
+        assert!(read_only(archive.ensure_index().map(drop)));
+        assert_eq!(
+            workspace_manager.move_file("notes", "todo", "archive"),
+            Err(WorkspaceError::ReadOnly("archive".to_string()))
+        );
+        assert_eq!(
+            workspace_manager.move_file("archive", "old", "notes"),
+            Err(WorkspaceError::ReadOnly("archive".to_string()))
+        );
+
+        assert!(archive.file_exists("old"));
+        assert!(!archive.file_exists("new"));
+        assert!(!archive.has_index());
+    }
+
+    #[test]
+    fn test_on_create() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let mut workspace_manager =
+            WorkspaceManager::from_single_workspace(Workspace::with_path("notes", dir.path()))
+                .unwrap();
+
+        let created = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for hook in ["first", "second"] {
+            let created = Arc::clone(&created);
+            workspace_manager.on_create(Box::new(move |workspace, path| {
+                created.lock().unwrap().push(format!(
+                    "{hook} {} {}",
+                    workspace.name,
+                    path.file_name().unwrap().to_string_lossy()
+                ));
+            }));
+        }
+
+        workspace_manager
+            .create_file("notes", "todo")
+            .expect("Unable to create the note");
+        workspace_manager
+            .write_file("notes", "todo", "* Heading", WriteMode::Overwrite)
+            .expect("Unable to write the note");
+        workspace_manager
+            .write_file("notes", "done", "", WriteMode::Append)
+            .expect("Unable to write the note");
+        assert!(workspace_manager.create_file("notes", "todo").is_err());
+        assert!(workspace_manager.create_file("missing", "todo").is_err());
+
+        assert_eq!(
+            *created.lock().unwrap(),
+            vec![
+                "first notes todo.norg",
+                "second notes todo.norg",
+                "first notes done.norg",
+                "second notes done.norg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_state() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let state = dir.path().join("state");
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", "/notes")
+            .workspace("work", "/work")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        workspace_manager.set_current_workspace("work").unwrap();
+        workspace_manager
+            .save_state(&state)
+            .expect("Unable to save the state");
+
+        let mut restored = workspace_manager.clone();
+        restored.reset_to_default();
+        restored
+            .load_state(&state)
+            .expect("Unable to load the state");
+        assert_eq!(restored.current_workspace_name(), Some("work"));
+
+        restored.clear_current_workspace();
+        restored.remove_workspace("work").unwrap();
+        restored
+            .load_state(&state)
+            .expect("A missing workspace must not be an error");
+        assert_eq!(restored.current_workspace_name(), Some("notes"));
+
+        workspace_manager
+            .load_state(&dir.path().join("missing"))
+            .expect("A missing state file must not be an error");
+        assert_eq!(workspace_manager.current_workspace_name(), Some("notes"));
+    }
+
+    #[test]
+    fn test_write_new() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let path = dir.path().join("index.norg");
+
+        write_new(&path, b"* Index").expect("Unable to write the file");
+        assert_eq!(fs::read(&path).unwrap(), b"* Index");
+        assert_eq!(
+            write_new(&path, b"").unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"* Index");
+    }
+
+    #[test]
+    fn test_rename_file() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("notes", dir.path());
+        let from = workspace
+            .create_file("projects/todo")
+            .expect("Unable to create the note");
+        workspace.create_file("done").unwrap();
+
+        let to = workspace
+            .rename_file("projects/todo", "archive/2024/todo")
+            .expect("Unable to rename the note");
+        assert_eq!(
+            to,
+            std::path::absolute(dir.path().join("archive/2024/todo.norg")).unwrap()
+        );
+        assert!(!from.exists());
+        assert!(to.is_file());
+
+        let rename_error = |from_rel, to_rel| {
+            io::Error::from(workspace.rename_file(from_rel, to_rel).unwrap_err()).kind()
+        };
+        assert_eq!(
+            rename_error("archive/2024/todo", "done"),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(rename_error("missing", "new"), io::ErrorKind::NotFound);
+        assert_eq!(
+            rename_error("missing", "elsewhere/new"),
+            io::ErrorKind::NotFound
+        );
+        assert!(!dir.path().join("elsewhere").exists());
+        assert_eq!(
+            rename_error("done", "../escape"),
+            io::ErrorKind::InvalidInput
+        );
+        assert!(workspace.file_exists("done"));
+        assert!(to.is_file(), "a failed rename must keep the source");
+    }
+
+    #[test]
+    fn test_ensure_index() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().join("notes"));
+
+        assert!(!workspace.has_index());
+        let index = workspace
+            .ensure_index()
+            .expect("Unable to create the index");
+        assert_eq!(index, dir.path().join("notes/index.norg"));
+        assert!(workspace.has_index());
+
+        fs::write(&index, "* Index").unwrap();
+        workspace
+            .ensure_index()
+            .expect("An existing index must be kept");
+        assert_eq!(fs::read_to_string(&index).unwrap(), "* Index");
+
+        let main = workspace
+            .ensure_index_named("main.norg")
+            .expect("Unable to create the index");
+        assert_eq!(main, dir.path().join("notes/main.norg"));
+    }
+
+    #[test]
+    fn test_parent_index() {
+        let filesystem = Arc::new(MemoryFileSystem::new());
+        let workspace = Workspace::with_path("notes", "/notes").with_filesystem(filesystem);
+        let parent_index = |rel: &str| workspace.parent_index(Path::new(rel));
+
+        assert_eq!(parent_index("projects/todo.norg"), None);
+
+        workspace.ensure_index().unwrap();
+        workspace.create_file("projects/index").unwrap();
+        assert_eq!(
+            parent_index("projects/todo.norg"),
+            Some(PathBuf::from("/notes/projects/index.norg"))
+        );
+        assert_eq!(
+            parent_index("projects/a/b/todo.norg"),
+            Some(PathBuf::from("/notes/projects/index.norg"))
+        );
+        assert_eq!(
+            parent_index("projects/index.norg"),
+            Some(PathBuf::from("/notes/index.norg"))
+        );
+        assert_eq!(parent_index("index.norg"), None);
+        assert_eq!(parent_index("../todo.norg"), None);
+    }
+
+    #[test]
+    fn test_memory_file_system() {
+        let filesystem = Arc::new(MemoryFileSystem::new());
+        let workspace_manager = WorkspaceManager::without_current_workspace(vec![
+            Workspace::with_path("notes", "/notes").with_filesystem(filesystem.clone()),
+            Workspace::with_path("work", "/work").with_filesystem(filesystem.clone()),
+        ])
+        .unwrap();
+        let notes = workspace_manager.get_workspace("notes").unwrap();
+
+        let path = workspace_manager
+            .create_file("notes", "projects/todo")
+            .expect("Unable to create the note");
+        assert_eq!(path, PathBuf::from("/notes/projects/todo.norg"));
+        assert!(notes.file_exists("projects/todo"));
+        assert!(!path.exists());
+
+        notes
+            .write_file("projects/todo", "* Todo", WriteMode::Append)
+            .unwrap();
+        notes.ensure_index().unwrap();
+        assert!(notes.has_index());
+
+        let to = workspace_manager
+            .move_file("notes", "projects/todo", "work")
+            .expect("Unable to move the note");
+        assert!(!notes.file_exists("projects/todo"));
+        assert_eq!(filesystem.read(&to).unwrap(), b"* Todo");
+
+        let work = workspace_manager.get_workspace("work").unwrap();
+        work.delete_file("projects/todo", DeleteMode::Trash)
+            .expect("Unable to delete the note");
+        assert!(!work.file_exists("projects/todo"));
+        assert_eq!(
+            filesystem
+                .read_dir(&Path::new("/work").join(TRASH_FOLDER).join("projects"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_move_file() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", dir.path().join("notes"))
+            .workspace("work", dir.path().join("work"))
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let from = workspace_manager
+            .get_workspace("notes")
+            .unwrap()
+            .create_file("projects/todo")
+            .expect("Unable to create the note");
+
+        let to = workspace_manager
+            .move_file("notes", "projects/todo", "work")
+            .expect("Unable to move the note");
+        assert_eq!(to, dir.path().join("work/projects/todo.norg"));
+        assert!(!from.exists());
+        assert!(to.is_file());
+
+        fs::write(&from, "").unwrap();
+        assert_eq!(
+            workspace_manager.move_file("notes", "projects/todo", "work"),
+            Err(WorkspaceError::AlreadyExists(to.display().to_string()))
+        );
+        assert_eq!(
+            workspace_manager.move_file("notes", "projects/todo", "missing"),
+            Err(WorkspaceError::NotFound(WorkspaceNotFound {
+                workspace: "missing".to_string()
+            }))
+        );
+
+        let err = workspace_manager
+            .move_file("notes", "missing", "work")
+            .expect_err("A missing note must be rejected");
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+    }
+    #[test]
+    fn test_move_file_between_filesystems() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let filesystem = Arc::new(MemoryFileSystem::new());
+        let workspace_manager = WorkspaceManager::without_current_workspace(vec![
+            Workspace::with_path("notes", "/notes").with_filesystem(filesystem.clone()),
+            Workspace::with_path("work", dir.path().join("work")),
+        ])
+        .unwrap();
+
+        workspace_manager
+            .write_file("notes", "projects/todo", "* Todo", WriteMode::CreateNew)
+            .unwrap();
+        let to = workspace_manager
+            .move_file("notes", "projects/todo", "work")
+            .expect("Unable to move the note");
+        assert_eq!(to, dir.path().join("work/projects/todo.norg"));
+        assert_eq!(fs::read_to_string(&to).unwrap(), "* Todo");
+        assert!(!filesystem.exists(Path::new("/notes/projects/todo.norg")));
+
+        workspace_manager
+            .write_file("notes", "projects/todo", "* Other", WriteMode::CreateNew)
+            .unwrap();
+        assert_eq!(
+            workspace_manager.plan_move_file("notes", "projects/todo", "work"),
+            Err(WorkspaceError::AlreadyExists(to.display().to_string()))
+        );
+        assert_eq!(
+            workspace_manager.move_file("notes", "projects/todo", "work"),
+            Err(WorkspaceError::AlreadyExists(to.display().to_string()))
+        );
+        assert!(filesystem.exists(Path::new("/notes/projects/todo.norg")));
+        assert_eq!(fs::read_to_string(&to).unwrap(), "* Todo");
+    }
+}
@@ -1,20 +1,473 @@
-use crate::workspace::Workspace;
-use std::path::PathBuf;
+use crate::filesystem::FileSystem;
+use crate::ignore::IgnoreRules;
+use crate::workspace::{Workspace, WorkspaceManager};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// The extension of files recognized as notes unless configured otherwise.
+pub const NORG_EXTENSION: &str = "norg";
+
+/// Aggregate statistics about the notes in a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WorkspaceStats {
+    /// The number of `.norg` files.
+    pub notes: usize,
+    /// The combined size of all `.norg` files, in bytes.
+    pub total_bytes: u64,
+    /// The most recent modification time of any `.norg` file, or None if there are none.
+    pub last_modified: Option<SystemTime>,
+}
+
+/// Whether walking a workspace descends into symbolic links to directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FollowSymlinks {
+    /// Symbolic links to directories are skipped, so the walk stays inside the workspace.
+    #[default]
+    Never,
+    /// Symbolic links to directories are traversed, skipping any link that points back
+    /// at one of its own ancestors. The reported paths go through the link, but the files
+    /// they refer to may lie outside the workspace root, e.g. in another workspace.
+    Always,
+}
+
+fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+    entry.file_name().to_str().unwrap_or(".").starts_with('.')
+}
+
+/// Walks a directory through a [`FileSystem`] rather than `walkdir`, with the same
+/// filtering as [`Workspace::walk_files`]. Symbolic links are up to the filesystem.
+fn walk_dir(
+    filesystem: &dyn FileSystem,
+    root: &Path,
+    dir: &Path,
+    ignore: &IgnoreRules,
+    visit: &mut impl FnMut(PathBuf) -> ControlFlow<()>,
+) -> io::Result<ControlFlow<()>> {
+    for path in filesystem.read_dir(dir)? {
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(".")
+            .starts_with('.');
+        let is_dir = filesystem.is_dir(&path);
+        let ignored = path
+            .strip_prefix(root)
+            .is_ok_and(|relative| ignore.is_ignored(relative, is_dir));
+
+        if hidden || ignored {
+            continue;
+        }
+
+        let flow = if is_dir {
+            walk_dir(filesystem, root, &path, ignore, visit)?
+        } else if filesystem.is_file(&path) {
+            visit(path)
+        } else {
+            ControlFlow::Continue(())
+        };
+
+        if flow.is_break() {
+            return Ok(flow);
+        }
+    }
+
+    Ok(ControlFlow::Continue(()))
+}
 
 impl Workspace {
-    pub fn files(&self) -> Vec<PathBuf> {
-        walkdir::WalkDir::new(&self.path)
-            .min_depth(1)
+    /// Returns all `.norg` files in the workspace, relative to the workspace root.
+    /// Hidden files and directories (those starting with a `.`) are skipped, as are those
+    /// matching the patterns of a [`.norgignore`](crate::ignore::IGNORE_FILE_NAME) file
+    /// at the workspace root. Other note extensions can be recognized through
+    /// [`Workspace::with_extensions`]; both apply to all methods listing notes.
+    ///
+    /// Symbolic links to directories are not traversed, so symlink loops cannot occur.
+    /// Use [`Workspace::files_with`] to follow them.
+    pub fn files(&self) -> io::Result<Vec<PathBuf>> {
+        self.files_with(FollowSymlinks::Never)
+    }
+
+    /// Like [`Workspace::files`], but controlling whether symbolic links to directories
+    /// are traversed.
+    ///
+    /// * `follow`: Whether to follow symbolic links to directories.
+    pub fn files_with(&self, follow: FollowSymlinks) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        self.for_each_file_with(follow, |path| {
+            files.push(path.to_path_buf());
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(files)
+    }
+
+    /// Calls a function with every `.norg` file in the workspace as it is discovered,
+    /// relative to the workspace root, without collecting them first.
+    /// Follows the same filtering rules as [`Workspace::files`].
+    ///
+    /// * `f`: The function to call. Returning [`ControlFlow::Break`] stops the walk.
+    pub fn for_each_file<F: FnMut(&Path) -> ControlFlow<()>>(&self, f: F) -> io::Result<()> {
+        self.for_each_file_with(FollowSymlinks::Never, f)
+    }
+
+    /// Like [`Workspace::for_each_file`], but controlling whether symbolic links to
+    /// directories are traversed.
+    ///
+    /// * `follow`: Whether to follow symbolic links to directories.
+    /// * `f`: The function to call. Returning [`ControlFlow::Break`] stops the walk.
+    pub fn for_each_file_with<F: FnMut(&Path) -> ControlFlow<()>>(
+        &self,
+        follow: FollowSymlinks,
+        mut f: F,
+    ) -> io::Result<()> {
+        let root = std::path::absolute(&self.path)?;
+
+        self.walk_files_with(follow, |path| {
+            if !self.is_note(&path) {
+                return ControlFlow::Continue(());
+            }
+
+            match path.strip_prefix(&root) {
+                Ok(relative) => f(relative),
+                Err(_) => f(&path),
+            }
+        })
+    }
+
+    /// Returns all `.norg` files in the workspace as absolute paths.
+    /// Follows the same filtering rules as [`Workspace::files`].
+    pub fn files_absolute(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        self.walk_files(|path| {
+            if self.is_note(&path) {
+                files.push(path);
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(files)
+    }
+
+    /// Returns all files in the workspace that are not notes, e.g. images and PDFs
+    /// referenced by notes, relative to the workspace root and sorted.
+    /// Follows the same filtering rules as [`Workspace::files`].
+    pub fn attachments(&self) -> io::Result<Vec<PathBuf>> {
+        self.attachments_with_extensions(&[])
+    }
+
+    /// Like [`Workspace::attachments`], but only returning files with one of the given
+    /// extensions, compared case-insensitively. An empty list returns all attachments.
+    ///
+    /// * `extensions`: The extensions to return, without the leading `.`, e.g. `["png", "pdf"]`.
+    pub fn attachments_with_extensions(&self, extensions: &[&str]) -> io::Result<Vec<PathBuf>> {
+        let root = std::path::absolute(&self.path)?;
+        let mut attachments = Vec::new();
+
+        self.walk_files(|path| {
+            let allowed = extensions.is_empty()
+                || path.extension().is_some_and(|extension| {
+                    extensions
+                        .iter()
+                        .any(|allowed| extension.eq_ignore_ascii_case(allowed))
+                });
+
+            if allowed && !self.is_note(&path) {
+                if let Ok(relative) = path.strip_prefix(&root) {
+                    attachments.push(relative.to_path_buf());
+                }
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        attachments.sort();
+
+        Ok(attachments)
+    }
+
+    /// Returns all `.norg` files in the workspace together with their modification time,
+    /// relative to the workspace root and sorted by modification time.
+    /// Files whose metadata cannot be read are skipped.
+    ///
+    /// * `newest_first`: Whether the most recently modified files come first.
+    pub fn files_by_mtime(&self, newest_first: bool) -> io::Result<Vec<(PathBuf, SystemTime)>> {
+        self.ensure_std_filesystem("sorting notes by modification time")?;
+        let root = std::path::absolute(&self.path)?;
+        let mut files = Vec::new();
+
+        self.walk_files(|path| {
+            if self.is_note(&path) {
+                if let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) {
+                    let relative = path.strip_prefix(&root).map(Path::to_path_buf);
+                    files.push((relative.unwrap_or(path), modified));
+                }
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        files.sort_by(|(a_path, a), (b_path, b)| a.cmp(b).then_with(|| a_path.cmp(b_path)));
+
+        if newest_first {
+            files.reverse();
+        }
+
+        Ok(files)
+    }
+
+    /// Returns all `.norg` files in the workspace grouped by category, i.e. by the top-level
+    /// directory containing them, with root-level notes under the empty string.
+    /// The paths are relative to the workspace root, like in [`Workspace::files`],
+    /// and sorted within each category.
+    pub fn files_grouped(&self) -> io::Result<HashMap<String, Vec<PathBuf>>> {
+        let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+        self.for_each_file(|path| {
+            let category = match path.components().count() {
+                1 => String::new(),
+                _ => path
+                    .components()
+                    .next()
+                    .map(|category| category.as_os_str().to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            };
+
+            groups.entry(category).or_default().push(path.to_path_buf());
+            ControlFlow::Continue(())
+        })?;
+
+        for files in groups.values_mut() {
+            files.sort_unstable();
+        }
+
+        Ok(groups)
+    }
+
+    /// Returns the number of `.norg` files per category, i.e. per top-level directory,
+    /// with root-level notes counted under the empty string like in
+    /// [`Workspace::files_grouped`]. Every category containing notes at any depth is
+    /// present, even if it has no notes directly inside it.
+    ///
+    /// * `recursive`: Whether notes in subdirectories of a category are counted as well,
+    ///   rather than only those directly inside it.
+    pub fn category_counts(&self, recursive: bool) -> io::Result<HashMap<String, usize>> {
+        Ok(self
+            .files_grouped()?
             .into_iter()
-            .filter(|e| match e {
-                Ok(entry) => {
-                    !entry.file_name().to_str().unwrap_or(".").starts_with('.')
-                        && !entry.path().is_dir()
+            .map(|(category, files)| {
+                let count = files
+                    .iter()
+                    .filter(|path| recursive || path.components().count() <= 2)
+                    .count();
+
+                (category, count)
+            })
+            .collect())
+    }
+
+    /// Returns all `.norg` files in the workspace whose name without the extension is
+    /// `basename`, relative to the workspace root and sorted. Every match is returned so
+    /// that the caller can disambiguate notes with the same name in different directories.
+    /// Follows the same filtering rules as [`Workspace::files`].
+    ///
+    /// * `basename`: The name of the note, e.g. `todo`.
+    /// * `case_sensitive`: Whether `Todo` and `todo` are considered different names.
+    pub fn find_by_name(&self, basename: &str, case_sensitive: bool) -> io::Result<Vec<PathBuf>> {
+        let basename = if case_sensitive {
+            basename.to_string()
+        } else {
+            basename.to_lowercase()
+        };
+        let mut files = Vec::new();
+
+        self.for_each_file(|path| {
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                let matches = if case_sensitive {
+                    stem == basename
+                } else {
+                    stem.to_lowercase() == basename
+                };
+
+                if matches {
+                    files.push(path.to_path_buf());
+                }
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        files.sort_unstable();
+
+        Ok(files)
+    }
+
+    /// Returns the number, combined size and latest modification time of all `.norg` files
+    /// in the workspace, computed in a single walk. Files whose metadata cannot be read
+    /// are skipped.
+    pub fn stats(&self) -> io::Result<WorkspaceStats> {
+        self.ensure_std_filesystem("computing workspace statistics")?;
+        let mut stats = WorkspaceStats::default();
+
+        self.walk_files(|path| {
+            if self.is_note(&path) {
+                if let Ok(metadata) = path.metadata() {
+                    stats.notes += 1;
+                    stats.total_bytes += metadata.len();
+                    stats.last_modified = stats.last_modified.max(metadata.modified().ok());
                 }
-                Err(_) => false,
+            }
+
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(stats)
+    }
+
+    /// Returns the names of the immediate subdirectories of the workspace, which Neorg
+    /// users treat as categories, sorted by name. Hidden directories such as the trash
+    /// are skipped, as are names that are not valid UTF-8.
+    pub fn categories(&self) -> io::Result<Vec<String>> {
+        let filesystem = self.filesystem();
+        let mut categories: Vec<String> = filesystem
+            .read_dir(&self.path)?
+            .into_iter()
+            .filter(|path| filesystem.is_dir(path))
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+
+        categories.sort_unstable();
+
+        Ok(categories)
+    }
+
+    /// Visits the absolute path of every non-hidden file in the workspace,
+    /// stopping early if the visitor returns [`ControlFlow::Break`].
+    pub(crate) fn walk_files(
+        &self,
+        visit: impl FnMut(PathBuf) -> ControlFlow<()>,
+    ) -> io::Result<()> {
+        self.walk_files_with(FollowSymlinks::Never, visit)
+    }
+
+    fn walk_files_with(
+        &self,
+        follow: FollowSymlinks,
+        mut visit: impl FnMut(PathBuf) -> ControlFlow<()>,
+    ) -> io::Result<()> {
+        let root = std::path::absolute(&self.path)?;
+        let ignore = IgnoreRules::load(&root, self.filesystem())?;
+
+        if let Some(filesystem) = &self.filesystem {
+            return walk_dir(filesystem.as_ref(), &root, &root, &ignore, &mut visit).map(drop);
+        }
+
+        for entry in walkdir::WalkDir::new(&root)
+            .min_depth(1)
+            .follow_links(follow == FollowSymlinks::Always)
+            .into_iter()
+            .filter_entry(|entry| {
+                !is_hidden(entry)
+                    && !entry.path().strip_prefix(&root).is_ok_and(|relative| {
+                        ignore.is_ignored(relative, entry.file_type().is_dir())
+                    })
             })
-            .map(|file| file.unwrap().into_path())
-            .collect()
+        {
+            let entry = match entry {
+                // A link back to an ancestor would be walked forever, so it is skipped.
+                Err(err) if err.loop_ancestor().is_some() => continue,
+                entry => entry?,
+            };
+
+            if entry.path().is_file() && visit(entry.into_path()).is_break() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WorkspaceManager {
+    /// Returns every `.norg` file of every workspace as a pair of the workspace name and the
+    /// path relative to that workspace, ordered by workspace name.
+    ///
+    /// This is all-or-nothing: the first workspace that cannot be traversed aborts the walk.
+    /// Call [`Workspace::files`] on each of [`WorkspaceManager::workspaces`] to handle
+    /// errors per workspace instead.
+    pub fn all_files(&self) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+
+        for name in self.names_sorted() {
+            self.workspaces[name].for_each_file(|path| {
+                files.push((name.to_string(), path.to_path_buf()));
+                ControlFlow::Continue(())
+            })?;
+        }
+
+        Ok(files)
+    }
+
+    /// Returns the `limit` most recently modified notes across all workspaces, newest first,
+    /// as triples of the workspace name, the path relative to that workspace and the
+    /// modification time. Files whose metadata cannot be read are skipped.
+    ///
+    /// Like [`WorkspaceManager::all_files`], the first workspace that cannot be traversed
+    /// aborts the walk.
+    ///
+    /// * `limit`: The maximum number of notes to return.
+    pub fn recent_files(&self, limit: usize) -> io::Result<Vec<(String, PathBuf, SystemTime)>> {
+        // A min-heap of the newest notes seen so far, so only `limit` entries are ever kept.
+        let mut newest: BinaryHeap<Reverse<(SystemTime, &str, PathBuf)>> = BinaryHeap::new();
+
+        for workspace in self.workspaces() {
+            workspace.ensure_std_filesystem("listing recent notes")?;
+            let root = std::path::absolute(&workspace.path)?;
+
+            workspace.walk_files(|path| {
+                if limit == 0 || !workspace.is_note(&path) {
+                    return ControlFlow::Continue(());
+                }
+                let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) else {
+                    return ControlFlow::Continue(());
+                };
+
+                let is_newer = newest.len() < limit
+                    || newest
+                        .peek()
+                        .is_some_and(|Reverse((oldest, ..))| modified > *oldest);
+
+                if is_newer {
+                    let relative = path.strip_prefix(&root).map(Path::to_path_buf);
+
+                    if newest.len() == limit {
+                        newest.pop();
+                    }
+                    newest.push(Reverse((
+                        modified,
+                        &workspace.name,
+                        relative.unwrap_or(path),
+                    )));
+                }
+
+                ControlFlow::Continue(())
+            })?;
+        }
+
+        Ok(newest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((modified, name, path))| (name.to_string(), path, modified))
+            .collect())
     }
 }
 
@@ -24,13 +477,422 @@ mod tests {
 
     #[test]
     fn test_files() {
-        let workspace = Workspace {
-            name: "example workspace".to_string(),
-            path: PathBuf::from("test/example_workspace"),
-        };
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
 
-        let files = workspace.files();
+        let files = workspace.files().expect("Unable to list workspace files");
         println!("{files:#?}");
         assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.is_relative()));
+        assert!(files.contains(&PathBuf::from("file1.norg")));
+    }
+
+    #[test]
+    fn test_files_absolute() {
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
+
+        let files = workspace
+            .files_absolute()
+            .expect("Unable to list workspace files");
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.is_absolute()));
+    }
+
+    #[test]
+    fn test_for_each_file() {
+        let workspace =
+            Workspace::with_path("example workspace", PathBuf::from("test/example_workspace"));
+
+        let mut visited = Vec::new();
+        workspace
+            .for_each_file(|path| {
+                visited.push(path.to_path_buf());
+                ControlFlow::Break(())
+            })
+            .expect("Unable to walk the workspace");
+
+        assert_eq!(visited.len(), 1);
+        assert!(visited[0].is_relative());
+    }
+
+    #[test]
+    fn test_files_by_mtime() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        for (name, secs) in [("old.norg", 1), ("new.norg", 3), ("middle.norg", 2)] {
+            std::fs::File::create(dir.path().join(name))
+                .and_then(|file| {
+                    file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                })
+                .expect("Unable to create the note");
+        }
+
+        let names = |newest_first| -> Vec<PathBuf> {
+            workspace
+                .files_by_mtime(newest_first)
+                .expect("Unable to list workspace files")
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        };
+
+        assert_eq!(
+            names(true),
+            vec![
+                PathBuf::from("new.norg"),
+                PathBuf::from("middle.norg"),
+                PathBuf::from("old.norg")
+            ]
+        );
+        assert_eq!(names(false).first(), Some(&PathBuf::from("old.norg")));
+    }
+
+    #[test]
+    fn test_stats() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        assert_eq!(workspace.stats().unwrap(), WorkspaceStats::default());
+
+        std::fs::write(dir.path().join("a.norg"), "* A").unwrap();
+        std::fs::write(dir.path().join("b.norg"), "* Bee").unwrap();
+        std::fs::write(dir.path().join("image.png"), "not a note").unwrap();
+
+        let stats = workspace.stats().expect("Unable to compute the stats");
+        assert_eq!(stats.notes, 2);
+        assert_eq!(stats.total_bytes, 8);
+        assert!(stats.last_modified.is_some());
+    }
+
+    #[test]
+    fn test_categories() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path().to_path_buf());
+
+        for category in ["work", "personal/journal", ".trash/work", ".git"] {
+            std::fs::create_dir_all(dir.path().join(category)).unwrap();
+        }
+        std::fs::write(dir.path().join("index.norg"), "").unwrap();
+
+        assert_eq!(
+            workspace
+                .categories()
+                .expect("Unable to list the categories"),
+            vec!["personal".to_string(), "work".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_all_files() {
+        let mut workspace_manager = WorkspaceManager::builder()
+            .workspace("b", "test/example_workspace")
+            .workspace("a", "test/example_workspace")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let files = workspace_manager
+            .all_files()
+            .expect("Unable to list workspace files");
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].0, "a");
+        assert_eq!(files[3].0, "b");
+        assert!(files.iter().all(|(_, file)| file.is_relative()));
+
+        workspace_manager
+            .add_workspace(Workspace::with_path(
+                "missing",
+                PathBuf::from("test/this_workspace_does_not_exist"),
+            ))
+            .unwrap();
+        assert!(workspace_manager.all_files().is_err());
+    }
+
+    #[test]
+    fn test_files_with_extensions() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace = Workspace::with_path("example workspace", dir.path())
+            .with_extensions(vec!["norg".to_string(), "md".to_string()]);
+
+        for file in ["todo.norg", "readme.md", "image.png"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let mut files = workspace.files().expect("Unable to list workspace files");
+        files.sort();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("readme.md"), PathBuf::from("todo.norg")]
+        );
+    }
+
+    #[test]
+    fn test_recent_files() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("notes")).unwrap();
+        std::fs::create_dir_all(dir.path().join("work")).unwrap();
+
+        for (name, secs) in [
+            ("notes/old.norg", 1),
+            ("work/new.norg", 4),
+            ("notes/middle.norg", 2),
+            ("work/newer.norg", 3),
+        ] {
+            std::fs::File::create(dir.path().join(name))
+                .and_then(|file| {
+                    file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                })
+                .expect("Unable to create the note");
+        }
+
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", dir.path().join("notes"))
+            .workspace("work", dir.path().join("work"))
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let recent: Vec<(String, PathBuf)> = workspace_manager
+            .recent_files(3)
+            .expect("Unable to list the recent files")
+            .into_iter()
+            .map(|(name, path, _)| (name, path))
+            .collect();
+        assert_eq!(
+            recent,
+            vec![
+                ("work".to_string(), PathBuf::from("new.norg")),
+                ("work".to_string(), PathBuf::from("newer.norg")),
+                ("notes".to_string(), PathBuf::from("middle.norg")),
+            ]
+        );
+        assert!(workspace_manager.recent_files(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("projects")).unwrap();
+        for name in [
+            "todo.norg",
+            "projects/Todo.norg",
+            "projects/todo.md",
+            "todos.norg",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+
+        assert_eq!(
+            workspace.find_by_name("todo", true).unwrap(),
+            vec![PathBuf::from("todo.norg")]
+        );
+        assert_eq!(
+            workspace.find_by_name("TODO", false).unwrap(),
+            vec![
+                PathBuf::from("projects/Todo.norg"),
+                PathBuf::from("todo.norg")
+            ]
+        );
+        assert!(workspace.find_by_name("missing", false).unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_follow_symlinks() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("notes")).unwrap();
+        std::fs::create_dir_all(dir.path().join("work")).unwrap();
+        std::fs::write(dir.path().join("notes/todo.norg"), "").unwrap();
+        std::fs::write(dir.path().join("work/plan.norg"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("work"), dir.path().join("notes/work")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("notes"), dir.path().join("notes/loop"))
+            .unwrap();
+
+        let workspace = Workspace::with_path("notes", dir.path().join("notes"));
+
+        assert_eq!(workspace.files().unwrap(), vec![PathBuf::from("todo.norg")]);
+
+        let mut files = workspace
+            .files_with(FollowSymlinks::Always)
+            .expect("Unable to follow the symlinks");
+        files.sort();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("todo.norg"), PathBuf::from("work/plan.norg")]
+        );
+    }
+
+    #[test]
+    fn test_files_grouped() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("projects/a")).unwrap();
+        for name in ["index.norg", "projects/b.norg", "projects/a/c.norg"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+        let groups = workspace.files_grouped().expect("Unable to list the files");
+
+        assert_eq!(
+            groups,
+            HashMap::from([
+                (String::new(), vec![PathBuf::from("index.norg")]),
+                (
+                    "projects".to_string(),
+                    vec![
+                        PathBuf::from("projects/a/c.norg"),
+                        PathBuf::from("projects/b.norg")
+                    ]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_files_norgignore() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("build")).unwrap();
+        for name in ["todo.norg", "draft.norg", "build/out.norg"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+        assert_eq!(workspace.files().unwrap().len(), 3);
+
+        std::fs::write(
+            dir.path().join(".norgignore"),
+            "build/
+draft.norg
+",
+        )
+        .unwrap();
+        assert_eq!(workspace.files().unwrap(), vec![PathBuf::from("todo.norg")]);
+    }
+
+    #[test]
+    fn test_attachments() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("images")).unwrap();
+        for name in [
+            "todo.norg",
+            "paper.pdf",
+            "images/diagram.PNG",
+            "images/.thumbnail.png",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+        assert_eq!(
+            workspace.attachments().unwrap(),
+            vec![
+                PathBuf::from("images/diagram.PNG"),
+                PathBuf::from("paper.pdf")
+            ]
+        );
+        assert_eq!(
+            workspace.attachments_with_extensions(&["png"]).unwrap(),
+            vec![PathBuf::from("images/diagram.PNG")]
+        );
+    }
+
+    #[test]
+    fn test_category_counts() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        std::fs::create_dir_all(dir.path().join("projects/a")).unwrap();
+        std::fs::create_dir_all(dir.path().join("archive/2023")).unwrap();
+        for name in [
+            "index.norg",
+            "projects/b.norg",
+            "projects/a/c.norg",
+            "archive/2023/d.norg",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let workspace = Workspace::with_path("example workspace", dir.path());
+        let counts = |recursive| {
+            let mut counts: Vec<(String, usize)> = workspace
+                .category_counts(recursive)
+                .expect("Unable to count the notes")
+                .into_iter()
+                .collect();
+            counts.sort();
+            counts
+        };
+
+        assert_eq!(
+            counts(false),
+            vec![
+                (String::new(), 1),
+                ("archive".to_string(), 0),
+                ("projects".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            counts(true),
+            vec![
+                (String::new(), 1),
+                ("archive".to_string(), 1),
+                ("projects".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files_memory_file_system() {
+        use crate::filesystem::MemoryFileSystem;
+        use crate::workspace_files::WriteMode;
+        use std::sync::Arc;
+
+        let filesystem = Arc::new(MemoryFileSystem::new());
+        let workspace = Workspace::with_path("notes", "/notes").with_filesystem(filesystem.clone());
+        for (rel, contents) in [
+            ("index", "* Index"),
+            ("projects/todo", "- ( ) Buy milk"),
+            ("build/out", ""),
+            (".hidden", ""),
+        ] {
+            workspace
+                .write_file(rel, contents, WriteMode::CreateNew)
+                .unwrap();
+        }
+        filesystem
+            .write(Path::new("/notes/.norgignore"), b"build/\n", true)
+            .unwrap();
+
+        let mut files = workspace.files().expect("Unable to list the files");
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("index.norg"),
+                PathBuf::from("projects/todo.norg")
+            ]
+        );
+        assert_eq!(workspace.categories().unwrap(), vec!["build", "projects"]);
+        assert_eq!(
+            workspace
+                .search("milk", crate::search::SearchOptions::default())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            workspace.stats().unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_files_missing_workspace() {
+        let workspace = Workspace::with_path(
+            "missing workspace",
+            PathBuf::from("test/this_workspace_does_not_exist"),
+        );
+
+        assert!(workspace.files().is_err());
     }
 }
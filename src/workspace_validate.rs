@@ -0,0 +1,198 @@
+use crate::path::canonicalize_lenient;
+use crate::workspace::{Workspace, WorkspaceManager};
+use std::{fmt, fs, io, path::PathBuf};
+
+/// A problem with a workspace's directory on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The workspace directory does not exist.
+    Missing,
+    /// The workspace path exists but is not a directory.
+    NotADirectory,
+    /// The workspace directory cannot be read.
+    PermissionDenied,
+    /// The workspace directory could not be accessed for another reason.
+    Other(io::ErrorKind),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Missing => write!(f, "directory does not exist"),
+            ValidationError::NotADirectory => write!(f, "not a directory"),
+            ValidationError::PermissionDenied => write!(f, "permission denied"),
+            ValidationError::Other(kind) => write!(f, "{kind}"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<io::Error> for ValidationError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => ValidationError::Missing,
+            io::ErrorKind::NotADirectory => ValidationError::NotADirectory,
+            io::ErrorKind::PermissionDenied => ValidationError::PermissionDenied,
+            kind => ValidationError::Other(kind),
+        }
+    }
+}
+
+impl Workspace {
+    /// Checks that the workspace path is an existing, readable directory.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(filesystem) = &self.filesystem {
+            if !filesystem.exists(&self.path) {
+                return Err(ValidationError::Missing);
+            }
+            if !filesystem.is_dir(&self.path) {
+                return Err(ValidationError::NotADirectory);
+            }
+            filesystem.read_dir(&self.path)?;
+
+            return Ok(());
+        }
+
+        if !fs::metadata(&self.path)?.is_dir() {
+            return Err(ValidationError::NotADirectory);
+        }
+
+        fs::read_dir(&self.path)?;
+
+        Ok(())
+    }
+}
+
+impl WorkspaceManager {
+    /// Checks every workspace with [`Workspace::validate`] and returns the problems found,
+    /// sorted by workspace name. An empty list means all workspaces are usable.
+    pub fn validate(&self) -> Vec<(&str, ValidationError)> {
+        let mut problems: Vec<(&str, ValidationError)> = self
+            .workspaces()
+            .filter_map(|w| w.validate().err().map(|err| (w.name.as_str(), err)))
+            .collect();
+
+        problems.sort_by_key(|(name, _)| *name);
+        problems
+    }
+
+    /// Returns the pairs of workspaces where the first workspace's directory contains
+    /// (or is the same as) the second's, sorted by name. Such workspaces make it ambiguous
+    /// which workspace a note belongs to.
+    ///
+    /// Paths are expanded and canonicalized before being compared, so `~/notes` and
+    /// `/home/user/notes` are recognized as the same directory. They do not need to exist.
+    pub fn overlapping(&self) -> Vec<(&str, &str)> {
+        let mut locations: Vec<(&str, PathBuf)> = self
+            .workspaces()
+            .filter_map(|w| Some((w.name.as_str(), w.location()?)))
+            .collect();
+        locations.sort();
+
+        let mut overlapping = Vec::new();
+
+        for (outer, outer_path) in &locations {
+            for (inner, inner_path) in &locations {
+                // Workspaces at the same location are only reported once.
+                let same = inner_path == outer_path;
+
+                if outer != inner && inner_path.starts_with(outer_path) && (!same || outer < inner)
+                {
+                    overlapping.push((*outer, *inner));
+                }
+            }
+        }
+
+        overlapping
+    }
+
+    /// Returns all workspaces ordered by the depth of their directory, most deeply nested
+    /// first and ties broken by name. Processing workspaces in this order resolves nested
+    /// workspaces before their ancestors, see [`WorkspaceManager::overlapping`].
+    ///
+    /// Paths are expanded and canonicalized like in [`WorkspaceManager::overlapping`];
+    /// workspaces whose path cannot be resolved come last.
+    pub fn workspaces_by_depth(&self) -> Vec<&Workspace> {
+        let mut workspaces: Vec<(usize, &Workspace)> = self
+            .workspaces()
+            .map(|w| (w.location().map_or(0, |path| path.components().count()), w))
+            .collect();
+        workspaces.sort_by(|(a_depth, a), (b_depth, b)| {
+            b_depth.cmp(a_depth).then_with(|| a.name.cmp(&b.name))
+        });
+
+        workspaces.into_iter().map(|(_, w)| w).collect()
+    }
+}
+
+impl Workspace {
+    /// Returns the expanded and canonicalized workspace directory, or None if it
+    /// cannot be resolved.
+    pub(crate) fn location(&self) -> Option<PathBuf> {
+        canonicalize_lenient(&self.expanded_path()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("valid", "test/example_workspace")
+            .workspace("missing", "test/this_workspace_does_not_exist")
+            .workspace("file", "test/example_workspace/file1.norg")
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.validate(),
+            vec![
+                ("file", ValidationError::NotADirectory),
+                ("missing", ValidationError::Missing),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", dir.path().join("notes"))
+            .workspace("projects", dir.path().join("notes/projects"))
+            .workspace("alias", dir.path().join("work/../notes"))
+            .workspace("work", dir.path().join("work"))
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        assert_eq!(
+            workspace_manager.overlapping(),
+            vec![
+                ("alias", "notes"),
+                ("alias", "projects"),
+                ("notes", "projects")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspaces_by_depth() {
+        let dir = tempfile::tempdir().expect("Unable to create a temporary directory");
+        let workspace_manager = WorkspaceManager::builder()
+            .workspace("notes", dir.path().join("notes"))
+            .workspace("projects", dir.path().join("notes/projects"))
+            .workspace("work", dir.path().join("work"))
+            .workspace("archive", dir.path().join("a/b/../archive"))
+            .build()
+            .expect("Unable to build the workspace manager");
+
+        let names: Vec<&str> = workspace_manager
+            .workspaces_by_depth()
+            .into_iter()
+            .map(|w| w.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["archive", "projects", "notes", "work"]);
+    }
+}